@@ -1,103 +1,484 @@
 use reqwest;
-use reqwest::header::{HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE};
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE, COOKIE, SET_COOKIE};
+use super::retry::{RequestBudget, RetryPolicy};
+use crate::error::Error;
+use futures_util::{future, stream, Stream, StreamExt, TryStreamExt};
+use crate::models::{Card, CardFilter, CardPatch, Comment, Credentials, CredentialsStore, CurrentUser, Profile};
+use reqwest::header::RETRY_AFTER;
 use std::env;
-use std::io;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+/// Kaiten allows 5 requests per second, keep a margin for other clients of the same token
+const DEFAULT_RATE_LIMIT: u32 = 4;
+
+/// Client of the Kaiten REST API with retries and rate limiting.
+/// It is `Send + Sync`, share one instance between tasks to share the rate limit
 pub struct ApiClient {
     client: reqwest::Client,
     base_api_url: reqwest::Url,
-    token: String,
+    /// `Bearer <token>`, checked once when the client is created
+    authorization: HeaderValue,
+    retry: RetryPolicy,
+    budget: RequestBudget,
+    token_source: TokenSource,
+}
+
+/// Where the token of the client was found, shown by `kaiten-cli whoami`
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenSource {
+    /// Passed to [`ApiClient::new`]
+    Explicit,
+    /// Environment variable with the token
+    Env(String),
+    /// `credentials.yaml` written by `kaiten-cli login`
+    Stored,
+}
+
+impl fmt::Display for TokenSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenSource::Explicit => write!(f, "explicit token"),
+            TokenSource::Env(name) => write!(f, "environment variable {}", name),
+            TokenSource::Stored => match CredentialsStore::path() {
+                Ok(path) => write!(f, "{}", path.display()),
+                Err(_) => write!(f, "credentials file"),
+            },
+        }
+    }
+}
+
+/// Credentials saved by `kaiten-cli login` for the profile, `None` without the file
+fn stored_credentials(profile: Option<&str>) -> Result<Option<Credentials>, Error> {
+    let store = CredentialsStore::load().map_err(|err| Error::Config(err.to_string()))?;
+    Ok(store.get(profile).cloned())
+}
+
+/// Read a number from the environment variable `name`, `default` when it is not set
+fn env_number<T: FromStr>(name: &str, default: T) -> Result<T, Error> {
+    match env::var(name) {
+        Ok(value) => value.trim().parse().map_err(|_| {
+            Error::Config(format!("Environment variable `{}` must be a number, got `{}`", name, value))
+        }),
+        Err(_) => Ok(default),
+    }
+}
+
+/// Network settings of [`ApiClient`]
+#[derive(Debug, Clone)]
+pub struct ClientSettings {
+    pub timeout: Duration,
+    pub retry: RetryPolicy,
+    /// Requests per second, `0` means no limit
+    pub rate_limit: u32,
+}
+
+impl Default for ClientSettings {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+            retry: RetryPolicy::default(),
+            rate_limit: DEFAULT_RATE_LIMIT,
+        }
+    }
+}
+
+impl ClientSettings {
+    /// Defaults overridden by `KAITEN_TIMEOUT`, `KAITEN_RETRIES` and `KAITEN_RATE_LIMIT`
+    pub fn from_env() -> Result<Self, Error> {
+        let default = Self::default();
+        Ok(Self {
+            timeout: Duration::from_secs(env_number("KAITEN_TIMEOUT", default.timeout.as_secs())?),
+            retry: RetryPolicy {
+                max_retries: env_number("KAITEN_RETRIES", default.retry.max_retries)?,
+                ..default.retry
+            },
+            rate_limit: env_number("KAITEN_RATE_LIMIT", default.rate_limit)?,
+        })
+    }
+}
+
+/// Headers for the log with the token and cookies hidden
+fn redacted(headers: &HeaderMap) -> String {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if name == AUTHORIZATION || name == COOKIE || name == SET_COOKIE {
+                "<redacted>"
+            } else {
+                value.to_str().unwrap_or("<binary>")
+            };
+            format!("{}: {}", name, value)
+        })
+        .collect::<Vec<String>>()
+        .join("; ")
+}
+
+/// Log the body of a successful response and give back an equal response,
+/// reqwest can read the body only once
+async fn trace_body(response: reqwest::Response) -> Result<reqwest::Response, Error> {
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = response.bytes().await?;
+    log::trace!("response body: {}", String::from_utf8_lossy(&body));
+    let mut traced = http::Response::new(body);
+    *traced.status_mut() = status;
+    *traced.headers_mut() = headers;
+    Ok(traced.into())
 }
 
 impl ApiClient {
-    pub fn default() -> Result<ApiClient, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
-        let base_api_url_str = env::var("API_URL")?;
-        let base_api_url = reqwest::Url::parse(&base_api_url_str)?;
-        let token = env::var("KT")?;
+    /// Client with the default settings.
+    /// `base_api_url` is like `https://company.kaiten.ru/api/latest/`, `token` is the API token
+    pub fn new(base_api_url: &str, token: &str) -> Result<ApiClient, Error> {
+        Self::with_settings(base_api_url, token, ClientSettings::default())
+    }
+
+    pub fn with_settings(
+        base_api_url: &str,
+        token: &str,
+        settings: ClientSettings,
+    ) -> Result<ApiClient, Error> {
+        let mut base_api_url = reqwest::Url::parse(base_api_url).map_err(|err| {
+            Error::Config(format!("API url {} is not valid: {}", base_api_url, err))
+        })?;
+        // without the trailing slash `join` would replace `latest` of `/api/latest`
+        if !base_api_url.path().ends_with('/') {
+            base_api_url.set_path(&format!("{}/", base_api_url.path()));
+        }
+        // a pasted token may contain a line break, which can't be sent in a header
+        let mut authorization = HeaderValue::from_str(&format!("Bearer {}", token)).map_err(|_| {
+            Error::Credentials(
+                "The API token contains a line break or another control character".to_string(),
+            )
+        })?;
+        authorization.set_sensitive(true);
+        let client = reqwest::Client::builder()
+            .timeout(settings.timeout)
+            .connect_timeout(settings.timeout.min(Duration::from_secs(10)))
+            .build()?;
         Ok(ApiClient {
             client,
             base_api_url,
-            token,
+            authorization,
+            retry: settings.retry,
+            budget: RequestBudget::per_second(settings.rate_limit),
+            token_source: TokenSource::Explicit,
         })
     }
 
+    /// Client configured by `API_URL`, `KT` and the `KAITEN_*` settings from the environment.
+    /// Without the variables the credentials of `kaiten-cli login` are used,
+    /// each variable which is set takes precedence over the saved value
+    pub fn from_env() -> Result<ApiClient, Error> {
+        let stored = match (env::var("API_URL"), env::var("KT")) {
+            (Ok(_), Ok(_)) => None,
+            _ => stored_credentials(None)?,
+        };
+        let base_api_url = match (env::var("API_URL"), &stored) {
+            (Ok(base_api_url), _) => base_api_url,
+            (Err(_), Some(stored)) => stored.api_url.clone(),
+            (Err(_), None) => {
+                return Err(Error::Credentials(
+                    "Environment variable `API_URL` is not set and there is no `kaiten-cli login`".to_string(),
+                ))
+            }
+        };
+        let (token, token_source) = match env::var("KT") {
+            Ok(token) => (token, TokenSource::Env("KT".to_string())),
+            // the saved token is never sent to another instance
+            Err(_) => match stored.filter(|stored| stored.is_for(&base_api_url)) {
+                Some(stored) => (stored.token, TokenSource::Stored),
+                None => {
+                    return Err(Error::Credentials(format!(
+                        "Environment variable `KT` is not set and there is no `kaiten-cli login` to {}",
+                        base_api_url
+                    )))
+                }
+            },
+        };
+        let mut client = Self::with_settings(&base_api_url, &token, ClientSettings::from_env()?)?;
+        client.token_source = token_source;
+        Ok(client)
+    }
+
+    /// Client of the `config.yaml` profile `name`, the token is read from its `token_env` variable
+    /// or from the credentials of `kaiten-cli --profile <name> login`
+    pub fn from_profile(name: &str, profile: &Profile) -> Result<ApiClient, Error> {
+        let token_env = profile.get_token_env();
+        let (token, token_source) = match env::var(token_env) {
+            Ok(token) => (token, TokenSource::Env(token_env.to_string())),
+            Err(_) => match stored_credentials(Some(name))?
+                .filter(|stored| stored.is_for(&profile.api_url))
+            {
+                Some(stored) => (stored.token, TokenSource::Stored),
+                None => {
+                    return Err(Error::Credentials(format!(
+                        "Environment variable `{}` with the token of the profile is not set, or run `kaiten-cli --profile {} login`",
+                        token_env, name
+                    )))
+                }
+            },
+        };
+        let mut client = Self::with_settings(&profile.api_url, &token, ClientSettings::from_env()?)?;
+        client.token_source = token_source;
+        Ok(client)
+    }
+
+    /// Base url with the trailing slash, like `https://company.kaiten.ru/api/latest/`
+    pub fn base_api_url(&self) -> &str {
+        self.base_api_url.as_str()
+    }
+
+    pub fn token_source(&self) -> &TokenSource {
+        &self.token_source
+    }
+
     fn common_headers(&self) -> reqwest::header::HeaderMap {
         let mut headers = reqwest::header::HeaderMap::new();
-        headers.insert(
-            AUTHORIZATION,
-            HeaderValue::from_str(&format!("Bearer {}", self.token)).unwrap(),
-        );
+        headers.insert(AUTHORIZATION, self.authorization.clone());
         headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
         headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
         headers
     }
-    async fn response_into_error(
-        &self,
-        response: reqwest::Response,
-    ) -> Result<reqwest::Response, Box<dyn std::error::Error>> {
+    async fn response_into_error(&self, response: reqwest::Response) -> Error {
         let url = response.url().to_string();
-        let text = response.text().await?;
-        let err_msg = format!("Request to url {} has failed. Error: {}", url, text);
-        let io_err = io::Error::new(io::ErrorKind::Other, err_msg);
-        Err(Box::new(io_err) as Box<dyn std::error::Error>)
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u64>().ok());
+        let text = response.text().await.unwrap_or_default();
+        let err_msg = format!("Request to url {} has failed with {}. Error: {}", url, status, text);
+        Error::from_status(status.as_u16(), err_msg, retry_after)
     }
-    async fn make_request<T>(
+    async fn make_request<T, Q>(
         &self,
         method: reqwest::Method,
         api_url: &str,
         data: Option<T>,
-    ) -> Result<reqwest::Response, Box<dyn std::error::Error>>
+        query: Option<&Q>,
+    ) -> Result<reqwest::Response, Error>
     where
         T: serde::Serialize,
+        Q: serde::Serialize,
     {
-        let url = self.base_api_url.join(api_url)?;
-        println!("{}", url);
-        let request = self
-            .client
-            .request(method, url)
-            .headers(self.common_headers());
-
-        let request = match data {
-            Some(data) => request.json(&data),
-            None => request,
-        };
+        let mut url = self
+            .base_api_url
+            .join(api_url)
+            .map_err(|err| Error::Invalid(format!("Invalid api url {}: {}", api_url, err)))?;
+        if let Some(query) = query {
+            // percent-encoded once by reqwest, so the logged url is the one which is sent
+            url = self
+                .client
+                .request(method.clone(), url)
+                .query(query)
+                .build()
+                .map_err(|err| Error::Invalid(format!("Invalid query of {}: {}", api_url, err)))?
+                .url()
+                .clone();
+        }
+        let body = data.map(|data| serde_json::to_vec(&data)).transpose()?;
+        // a repeated POST could create a second card or comment
+        let idempotent = matches!(
+            method,
+            reqwest::Method::GET | reqwest::Method::DELETE | reqwest::Method::PUT
+        );
+        let mut attempt = 0;
+        loop {
+            self.budget.acquire().await;
+            let request = self
+                .client
+                .request(method.clone(), url.clone())
+                .headers(self.common_headers());
+            let request = match &body {
+                Some(body) => request.body(body.clone()),
+                None => request,
+            };
+            log::debug!("request headers: {}", redacted(&self.common_headers()));
+            if let Some(body) = &body {
+                log::trace!("request body: {}", String::from_utf8_lossy(body));
+            }
 
-        let response = request.send().await?;
-        if response.status().is_success() {
-            Ok(response)
-        } else {
-            self.response_into_error(response).await
+            let started = Instant::now();
+            let result = request.send().await;
+            let elapsed_ms = started.elapsed().as_millis();
+            match &result {
+                Ok(response) => {
+                    log::info!(
+                        "method={} url={} status={} elapsed_ms={} attempt={}",
+                        method,
+                        url,
+                        response.status().as_u16(),
+                        elapsed_ms,
+                        attempt + 1
+                    );
+                    log::debug!("response headers: {}", redacted(response.headers()));
+                }
+                Err(err) => log::info!(
+                    "method={} url={} error=\"{}\" elapsed_ms={} attempt={}",
+                    method,
+                    url,
+                    err,
+                    elapsed_ms,
+                    attempt + 1
+                ),
+            }
+            let (error, retryable) = match result {
+                Ok(response) if response.status().is_success() => {
+                    if log::log_enabled!(log::Level::Trace) {
+                        return trace_body(response).await;
+                    }
+                    return Ok(response);
+                }
+                Ok(response) => {
+                    let error = self.response_into_error(response).await;
+                    let retryable = match error {
+                        Error::RateLimited { .. } => true,
+                        Error::Server(_) => idempotent,
+                        _ => false,
+                    };
+                    (error, retryable)
+                }
+                // nothing reached the server when the connection failed
+                Err(err) => {
+                    let retryable = err.is_connect() || (idempotent && err.is_timeout());
+                    (Error::from(err), retryable)
+                }
+            };
+            if !retryable || attempt >= self.retry.max_retries {
+                return Err(error);
+            }
+            let delay = match error {
+                Error::RateLimited {
+                    retry_after: Some(seconds),
+                    ..
+                } => Duration::from_secs(seconds),
+                _ => self.retry.backoff(attempt),
+            };
+            if delay > self.retry.max_delay {
+                return Err(error);
+            }
+            log::warn!("{}, retrying in {:.1}s", error, delay.as_secs_f32());
+            if let Error::RateLimited { .. } = error {
+                // concurrent requests wait as well, the next `acquire` sleeps until the pause ends
+                self.budget.pause(delay).await;
+            } else {
+                tokio::time::sleep(delay).await;
+            }
+            attempt += 1;
         }
     }
 
     pub async fn get_data(
         &self,
         api_url: &str,
-    ) -> Result<reqwest::Response, Box<dyn std::error::Error>> {
-        self.make_request::<()>(reqwest::Method::GET, api_url, None)
+    ) -> Result<reqwest::Response, Error> {
+        self.make_request::<(), ()>(reqwest::Method::GET, api_url, None, None)
             .await
     }
     pub async fn patch_data<T>(
         &self,
         api_url: &str,
         data: T,
-    ) -> Result<reqwest::Response, Box<dyn std::error::Error>>
+    ) -> Result<reqwest::Response, Error>
     where
         T: serde::Serialize,
     {
-        self.make_request(reqwest::Method::PATCH, api_url, Some(data))
+        self.make_request::<T, ()>(reqwest::Method::PATCH, api_url, Some(data), None)
+            .await
+    }
+    pub async fn delete_data(
+        &self,
+        api_url: &str,
+    ) -> Result<reqwest::Response, Error> {
+        self.make_request::<(), ()>(reqwest::Method::DELETE, api_url, None, None)
             .await
     }
     pub async fn post_data<T>(
         &self,
         api_url: &str,
         data: T,
-    ) -> Result<reqwest::Response, Box<dyn std::error::Error>>
+    ) -> Result<reqwest::Response, Error>
     where
         T: serde::Serialize,
     {
-        self.make_request(reqwest::Method::POST, api_url, Some(data))
+        self.make_request::<T, ()>(reqwest::Method::POST, api_url, Some(data), None)
             .await
     }
+
+    /// Owner of the token, also a cheap check that the url and the token are valid
+    pub async fn get_current_user(&self) -> Result<CurrentUser, Error> {
+        Ok(self.get_data("users/current").await?.json().await?)
+    }
+
+    /// Card with its members, tags, checklists and blockers
+    pub async fn get_card(&self, card_id: u32) -> Result<Card, Error> {
+        let api_url = format!("cards/{}", card_id);
+        Ok(self.get_data(&api_url).await?.json().await?)
+    }
+
+    /// One page of `GET /cards` matching `filter`, see [`CardFilter::page`].
+    /// Kaiten returns at most 100 cards per request
+    pub async fn list_cards(&self, filter: &CardFilter) -> Result<Vec<Card>, Error> {
+        Ok(self
+            .make_request::<(), _>(reqwest::Method::GET, "cards", None, Some(filter))
+            .await?
+            .json()
+            .await?)
+    }
+
+    /// Pages of `GET /cards` matching `filter` from its `offset`, until a short page.
+    /// Pages are requested ahead concurrently and come in order,
+    /// dropping the stream cancels the pages which are not needed
+    pub fn card_pages(
+        &self,
+        filter: &CardFilter,
+    ) -> impl Stream<Item = Result<Vec<Card>, Error>> + Unpin + '_ {
+        /// Kaiten returns at most 100 cards per request
+        const PAGE_SIZE: u32 = 100;
+        const CONCURRENT_PAGES: usize = 4;
+        let filter = filter.clone();
+        let start = filter.offset.unwrap_or(0);
+        let mut done = false;
+        stream::iter(0u32..)
+            .map(move |page| {
+                let page_filter = filter.page(PAGE_SIZE, start + page * PAGE_SIZE);
+                async move { self.list_cards(&page_filter).await }
+            })
+            .buffered(CONCURRENT_PAGES)
+            .take_while(move |page| {
+                // the short page and the first error are the last items
+                let more = !done;
+                done = page.as_ref().map_or(true, |page| page.len() < PAGE_SIZE as usize);
+                future::ready(more)
+            })
+    }
+
+    /// Every card matching `filter`, see [`ApiClient::card_pages`]
+    pub async fn list_all_cards(&self, filter: &CardFilter) -> Result<Vec<Card>, Error> {
+        self.card_pages(filter).try_concat().await
+    }
+
+    /// Send only the fields which are set in `patch`
+    pub async fn update_card(&self, card_id: u32, patch: &CardPatch) -> Result<(), Error> {
+        let api_url = format!("cards/{}", card_id);
+        let _ = self.patch_data(&api_url, patch).await?;
+        Ok(())
+    }
+
+    pub async fn get_comments(&self, card_id: u32) -> Result<Vec<Comment>, Error> {
+        let api_url = format!("cards/{}/comments", card_id);
+        Ok(self.get_data(&api_url).await?.json().await?)
+    }
+
+    /// Add a comment to the card, `text` is markdown with `@username` mentions
+    pub async fn add_comment(&self, card_id: u32, text: &str) -> Result<Comment, Error> {
+        let api_url = format!("cards/{}/comments", card_id);
+        let comment = Comment::from_text(text);
+        Ok(self.post_data(&api_url, comment).await?.json().await?)
+    }
 }
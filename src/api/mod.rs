@@ -1,3 +1,5 @@
-pub use client::ApiClient;
+pub use client::{ApiClient, ClientSettings, TokenSource};
+pub use retry::RetryPolicy;
 
 pub mod client;
+pub mod retry;
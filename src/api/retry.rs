@@ -0,0 +1,81 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// How failed requests are retried
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt, `0` disables retries
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    /// Upper bound for the backoff and for `Retry-After` of `429` responses
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff with jitter: between a half and the whole of `base_delay * 2^attempt`
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        let half = delay.as_millis() as u64 / 2;
+        Duration::from_millis(half + random() % (half + 1))
+    }
+}
+
+/// Random number without pulling a rand crate, `RandomState` is seeded randomly on every call
+fn random() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+/// Spaces requests out evenly so bulk commands stay under the Kaiten rate limit
+#[derive(Debug)]
+pub struct RequestBudget {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl RequestBudget {
+    /// `0` requests per second means no limit
+    pub fn per_second(requests: u32) -> Self {
+        let interval = if requests == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs(1) / requests
+        };
+        Self {
+            interval,
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Wait for the next free slot
+    pub async fn acquire(&self) {
+        let slot = {
+            let mut next = self.next.lock().await;
+            let slot = (*next).max(Instant::now());
+            *next = slot + self.interval;
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+
+    /// Hold every following request back, used after `429 Too Many Requests`
+    pub async fn pause(&self, duration: Duration) {
+        let mut next = self.next.lock().await;
+        *next = (*next).max(Instant::now() + duration);
+    }
+}
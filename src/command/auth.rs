@@ -0,0 +1,110 @@
+use super::context::Context;
+use super::input::{prompt, prompt_secret};
+use clap::Args;
+use kaiten_cli::api::{ApiClient, ClientSettings};
+use kaiten_cli::error::Error;
+use kaiten_cli::models::credentials::api_url_from_domain;
+use kaiten_cli::models::{Credentials, CredentialsStore};
+use std::io::{self, BufRead};
+
+#[derive(Args)]
+pub struct Login {
+    /// Kaiten domain like `company`, `company.kaiten.ru` or the API url, asked when not set
+    #[arg(long, value_parser = api_url_from_domain)]
+    domain: Option<String>,
+    /// Read the token from stdin instead of the hidden prompt, for scripts
+    #[arg(long)]
+    with_token: bool,
+}
+
+impl Login {
+    /// Check the token with `users/current` and save it for the profile in use
+    pub async fn execute(&self, ctx: &Context) -> Result<String, Error> {
+        let profile_name = ctx.config.get_profile_name();
+        let api_url = match (&self.domain, ctx.config.get_profile()) {
+            (Some(api_url), _) => api_url.to_owned(),
+            (None, Some(profile)) => profile.api_url.to_owned(),
+            (None, None) if self.with_token => {
+                return Err(Error::Invalid(
+                    "`--with-token` reads only the token, set the instance with `--domain`".to_string(),
+                ))
+            }
+            (None, None) => api_url_from_domain(&prompt("Kaiten domain (company.kaiten.ru)")?)
+                .map_err(Error::Invalid)?,
+        };
+        let credentials = Credentials {
+            api_url,
+            token: String::new(),
+        };
+        if let (Some(name), Some(profile)) = (profile_name, ctx.config.get_profile()) {
+            // the client of the profile uses only the token of its own instance
+            if !credentials.is_for(&profile.api_url) {
+                return Err(Error::Invalid(format!(
+                    "Profile `{}` is for {}, not {}",
+                    name, profile.api_url, credentials.api_url
+                )));
+            }
+        }
+        let token = if self.with_token {
+            let mut token = String::new();
+            io::stdin().lock().read_line(&mut token)?;
+            token.trim().to_string()
+        } else {
+            prompt_secret("API token (Kaiten profile, API/Integrations)")?
+        };
+        if token.is_empty() {
+            return Err(Error::Invalid("Token is empty".to_string()));
+        }
+        let client = ApiClient::with_settings(&credentials.api_url, &token, ClientSettings::from_env()?)?;
+        let user = client.get_current_user().await?;
+
+        let mut store = CredentialsStore::load().map_err(|err| Error::Config(err.to_string()))?;
+        store.set(
+            profile_name,
+            Credentials {
+                token,
+                ..credentials
+            },
+        );
+        store.save()?;
+        Ok(format!(
+            "Logged in to {} as {}, the token is saved to {}",
+            client.base_api_url(),
+            user.username,
+            CredentialsStore::path()?.display()
+        ))
+    }
+}
+
+/// Forget the saved token of the profile in use, the token itself stays valid in Kaiten
+pub fn logout(ctx: &Context) -> Result<String, Error> {
+    let profile_name = ctx.config.get_profile_name();
+    let mut store = CredentialsStore::load().map_err(|err| Error::Config(err.to_string()))?;
+    let credentials = store.remove(profile_name).ok_or_else(|| {
+        Error::Invalid(match profile_name {
+            Some(name) => format!("Profile `{}` is not logged in", name),
+            None => "Not logged in".to_string(),
+        })
+    })?;
+    store.save()?;
+    Ok(format!("Logged out of {}", credentials.api_url))
+}
+
+/// User of the token, the instance and where the token comes from
+pub async fn whoami(ctx: &Context) -> Result<String, Error> {
+    let client = ctx.client()?;
+    let user = client.get_current_user().await?;
+    let name = match &user.full_name {
+        Some(full_name) if !full_name.is_empty() => format!("{} ({})", user.username, full_name),
+        _ => user.username.to_owned(),
+    };
+    let mut lines = vec![
+        format!("User:     {}", name),
+        format!("Instance: {}", client.base_api_url()),
+        format!("Token:    {}", client.token_source()),
+    ];
+    if let Some(profile) = ctx.config.get_profile_name() {
+        lines.push(format!("Profile:  {}", profile));
+    }
+    Ok(lines.join("\n"))
+}
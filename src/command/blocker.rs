@@ -0,0 +1,158 @@
+use super::context::Context;
+use kaiten_cli::error::Error;
+use kaiten_cli::models::{BlockedCard, Blocker as ModelsBlocker, CardFilter, NewBlocker};
+use clap::{Args, Subcommand};
+use futures_util::{stream, StreamExt, TryStreamExt};
+use tabled::{
+    settings::{object::Columns, Style, Width},
+    Table,
+};
+
+#[derive(Args)]
+pub struct Blocker {
+    #[command(subcommand)]
+    pub command: BlockerCommands,
+}
+
+#[derive(Subcommand)]
+pub enum BlockerCommands {
+    /// get card blockers or all blocked cards on the board
+    Ls {
+        #[arg(required_unless_present = "board")]
+        card_id: Option<u32>,
+        /// List every blocked card on the current board
+        #[arg(long, conflicts_with = "card_id")]
+        board: bool,
+    },
+    /// block card with a reason and/or a blocking card
+    Add {
+        card_id: u32,
+        /// Free-text reason of the block
+        #[arg(long, short, required_unless_present = "card")]
+        reason: Option<String>,
+        /// Id of the card which blocks this one
+        #[arg(long, short)]
+        card: Option<u32>,
+    },
+    /// release card blocker, the only active one if id is not set
+    Release {
+        card_id: u32,
+        blocker_id: Option<u32>,
+    },
+}
+
+impl Blocker {
+    /// Blockers of the card, `None` for the board-wide list
+    pub fn get_url(&self) -> Option<String> {
+        match &self.command {
+            BlockerCommands::Ls {
+                card_id: Some(card_id),
+                ..
+            }
+            | BlockerCommands::Add { card_id, .. }
+            | BlockerCommands::Release { card_id, .. } => {
+                Some(format!("cards/{}/blockers", card_id))
+            }
+            BlockerCommands::Ls { card_id: None, .. } => None,
+        }
+    }
+    pub async fn get_table(&self, ctx: &Context) -> Result<String, Error> {
+        let api_url = self.get_url().unwrap_or_default();
+        let client = ctx.client()?;
+        let table = match &self.command {
+            BlockerCommands::Ls {
+                card_id: Some(_), ..
+            } => {
+                let blockers: Vec<ModelsBlocker> = client.get_data(&api_url).await?.json().await?;
+                Table::new(blockers)
+                    .modify(Columns::single(1), Width::wrap(60).keep_words())
+                    .with(Style::modern())
+                    .to_string()
+            }
+            BlockerCommands::Ls { card_id: None, .. } => {
+                const CONCURRENT_REQUESTS: usize = 8;
+                // without a board every blocked card of the instance would be fetched
+                let board_id = ctx.config.get_board_id().ok_or_else(|| {
+                    Error::Invalid(
+                        "Board is not set. Use `--board-id` or `kaiten-cli config set --board-id`"
+                            .to_string(),
+                    )
+                })?;
+                let filter = CardFilter {
+                    board_id: Some(board_id),
+                    ..CardFilter::not_done()
+                };
+                let cards = client.list_all_cards(&filter).await?;
+                let mut blocked: Vec<(usize, Vec<ModelsBlocker>)> =
+                    stream::iter(cards.iter().enumerate().filter(|(_, c)| c.is_blocked()))
+                        .map(|(idx, card)| async move {
+                            let api_url = format!("cards/{}/blockers", card.get_id());
+                            let blockers: Vec<ModelsBlocker> =
+                                client.get_data(&api_url).await?.json().await?;
+                            Ok::<_, Error>((idx, blockers))
+                        })
+                        .buffer_unordered(CONCURRENT_REQUESTS)
+                        .try_collect()
+                        .await?;
+                // keep the order of the cards
+                blocked.sort_by_key(|(idx, _)| *idx);
+                let rows: Vec<BlockedCard> = blocked
+                    .iter()
+                    .flat_map(|(idx, blockers)| {
+                        blockers
+                            .iter()
+                            .filter(|b| !b.is_released())
+                            .map(|b| BlockedCard::from(&cards[*idx], b))
+                    })
+                    .collect();
+                Table::new(rows)
+                    .modify(Columns::single(1), Width::wrap(50).keep_words())
+                    .modify(Columns::single(2), Width::wrap(50).keep_words())
+                    .with(Style::modern())
+                    .to_string()
+            }
+            BlockerCommands::Add {
+                card_id: _,
+                reason,
+                card,
+            } => {
+                let blocker = NewBlocker::from(reason.to_owned(), card.to_owned());
+                let _ = client.post_data(&api_url, blocker).await?;
+                String::new()
+            }
+            BlockerCommands::Release {
+                card_id,
+                blocker_id,
+            } => {
+                let blocker_id = match blocker_id {
+                    Some(blocker_id) => *blocker_id,
+                    None => {
+                        let blockers: Vec<ModelsBlocker> =
+                            client.get_data(&api_url).await?.json().await?;
+                        let active: Vec<u32> = blockers
+                            .iter()
+                            .filter(|b| !b.is_released())
+                            .map(|b| b.get_id())
+                            .collect();
+                        match active.as_slice() {
+                            [blocker_id] => *blocker_id,
+                            [] => return Err(Error::Invalid(format!("Card {} is not blocked", card_id))),
+                            ids => {
+                                let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+                                return Err(Error::Invalid(format!(
+                                    "Card {} has several blockers, choose one of: {}",
+                                    card_id,
+                                    ids.join(", ")
+                                )));
+                            }
+                        }
+                    }
+                };
+                let api_url = format!("{}/{}", api_url, blocker_id);
+                let _ = client.delete_data(&api_url).await?;
+                String::new()
+            }
+        };
+        Ok(table)
+    }
+}
@@ -1,13 +1,21 @@
-use crate::api::ApiClient;
-use crate::models::common::{CONFIG, INFO};
-use crate::models::{Card as ModelsCard, User};
-use clap::{Args, Subcommand};
+use super::input::{confirm, edit_text};
+use super::context::Context;
+use kaiten_cli::api::ApiClient;
+use kaiten_cli::error::Error;
+use kaiten_cli::models::common::eq_ignore_case;
+use kaiten_cli::models::due_date::parse_due_date;
+use kaiten_cli::models::view::CardView;
+use kaiten_cli::models::{
+    Card as ModelsCard, CardEditTemplate, CardFilter, Config, Info, CardFields, CardPatch, CardTemplate, CardTree, Checklist,
+    ChecklistChange, IdList, User,
+};
+use chrono::Local;
+use clap::{Args, Subcommand, ValueEnum};
+use futures_util::{stream, StreamExt, TryStreamExt};
+use std::collections::HashMap;
+use std::io::Write;
 use tabled::{
-    settings::{
-        measurement::Percent,
-        object::{Columns, Rows},
-        Modify, Style, Width,
-    },
+    settings::{object::Columns, Style, Width},
     Table,
 };
 
@@ -18,6 +26,7 @@ pub struct Card {
 }
 
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)]
 pub enum CardCommands {
     /// print all cards for user
     Ls(Ls),
@@ -25,34 +34,23 @@ pub enum CardCommands {
     Get {
         card_id: u32,
     },
-    /// edit card
-    Edit {
-        card_id: u32,
-        /// Set new desctiption for card
-        #[arg(long,short='d')]
-        description: Option<String>,
-        /// Add description for card
-        #[arg(long,short='D')]
-        add_description: Option<String>,
-        #[arg(long,short='t')]
-        title: Option<String>,
-        #[arg(long,short='t')]
-        column_id: Option<u32>,
-        #[arg(long,short='l')]
-        lane_id: Option<u32>,
-    },
-    /// create new card
+    /// edit card, without flags opens the whole card in $EDITOR
+    Edit(Edit),
+    /// create new card from a template opened in $EDITOR
     New {},
     /// move card to next column
     Mv {
         card_id: u32,
-        /// Column id to move
-        #[arg(long, short)]
-        column_id: u32,
-        /// Lane id to move
-        #[arg(long, short)]
-        lane_id: u32,
+        /// Column id or name to move, `Parent/Child` for subcolumns, `id:N` for another board
+        #[arg(long, short, visible_alias = "column-id")]
+        column: String,
+        /// Lane id or name to move, the first lane of the board when moving to another board
+        #[arg(long, short, visible_alias = "lane-id")]
+        lane: Option<String>,
         add_responsible: Option<String>,
+        /// Fail if the card was changed by someone else since it was read
+        #[arg(long)]
+        check_updated: bool,
     },
     Parents {
         card_id: u32,
@@ -60,16 +58,121 @@ pub enum CardCommands {
     Childrens {
         card_id: u32,
     },
+    /// add or remove card tags by name: `+bug -frontend`
+    Tag {
+        card_id: u32,
+        /// Tag names prefixed with `+` to add (default) or `-` to remove
+        #[arg(required = true, allow_hyphen_values = true, trailing_var_arg = true)]
+        tags: Vec<String>,
+    },
+    /// add or remove child card
+    Children {
+        action: ChildrenAction,
+        parent_id: u32,
+        child_id: u32,
+    },
+    /// show card descendants as a tree
+    Tree {
+        card_id: u32,
+        /// Maximum depth of descendants to fetch
+        #[arg(long, short, default_value = "3")]
+        depth: u32,
+    },
+    /// set or clear card due date
+    Due {
+        card_id: u32,
+        /// YYYY-MM-DD, `today`, `tomorrow`, weekday (`friday`), `+3d`, `+2w` or `clear`
+        #[arg(allow_hyphen_values = true)]
+        date: String,
+    },
+    /// manage card members
+    Members {
+        card_id: u32,
+        action: MembersAction,
+        /// Usernames, only one for `responsible`
+        #[arg(required = true)]
+        usernames: Vec<String>,
+    },
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum ChildrenAction {
+    Add,
+    Rm,
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum MembersAction {
+    /// add plain members
+    Add,
+    /// remove members
+    Rm,
+    /// make the user responsible for the card
+    Responsible,
+}
+
+#[derive(Args)]
+pub struct Edit {
+    card_id: u32,
+    /// Set new desctiption for card
+    #[arg(long,short='d')]
+    description: Option<String>,
+    /// Add description for card
+    #[arg(long,short='D')]
+    add_description: Option<String>,
+    #[arg(long,short='t')]
+    title: Option<String>,
+    /// Column id or name, `Parent/Child` for subcolumns, `id:N` for another board
+    #[arg(long, short = 'c', visible_alias = "column-id")]
+    column: Option<String>,
+    /// Lane id or name
+    #[arg(long, short = 'l', visible_alias = "lane-id")]
+    lane: Option<String>,
+    /// Card type letter or name, see `kaiten-cli types`
+    #[arg(long = "type", short = 'T')]
+    card_type: Option<String>,
+    /// Mark card as ASAP
+    #[arg(long, overrides_with = "no_asap")]
+    asap: bool,
+    /// Remove ASAP mark
+    #[arg(long, overrides_with = "asap")]
+    no_asap: bool,
+    /// Card size, e.g. `3` or `XL`
+    #[arg(long)]
+    size: Option<String>,
+    /// Fail if the card was changed by someone else since it was read
+    #[arg(long)]
+    check_updated: bool,
+}
+
+impl Edit {
+    fn get_asap(&self) -> Option<bool> {
+        match (self.asap, self.no_asap) {
+            (true, _) => Some(true),
+            (_, true) => Some(false),
+            _ => None,
+        }
+    }
+    fn has_changes(&self) -> bool {
+        self.description.is_some()
+            || self.add_description.is_some()
+            || self.title.is_some()
+            || self.column.is_some()
+            || self.lane.is_some()
+            || self.card_type.is_some()
+            || self.get_asap().is_some()
+            || self.size.is_some()
+    }
 }
 
 #[derive(Args)]
 pub struct Ls {
-    /// Filter by condition: 1 - on board, 2 - archived.
-    #[arg(short, long, default_value = "1")]
-    condition: u8,
-    /// Search by states filter, comma separated. 1-queued, 2-inProgress, 3-done.
-    #[arg(short = 'S', long, default_value = "1,2")]
-    states: String,
+    /// Filter by condition: 1 - on board, 2 - archived. Default: 1.
+    #[arg(short, long)]
+    condition: Option<u8>,
+    /// Search by states filter, comma separated. 1-queued, 2-inProgress, 3-done. Default: 1,2.
+    #[arg(short = 'S', long)]
+    states: Option<IdList>,
     #[arg(short, long)]
     properties_id: Option<u32>,
     #[arg(short = 'P', long)]
@@ -112,46 +215,52 @@ pub struct Ls {
     tag: Option<String>,
     /// Search by tag ids filter, comma separated.
     #[arg(long)]
-    tag_ids: Option<String>,
+    tag_ids: Option<IdList>,
     /// Search by type ids filter, comma separated.
     #[arg(long)]
-    type_ids: Option<String>,
-    /// Exclude board ids filter, comma separated.
+    type_ids: Option<IdList>,
+    /// Exclude board ids filter, comma separated, added to the config ones.
     #[arg(long)]
-    exclude_board_ids: Option<String>,
-    /// Exclude lane ids filter, comma separated.
+    exclude_board_ids: Option<IdList>,
+    /// Exclude lane ids filter, comma separated, added to the config ones.
     #[arg(long)]
-    exclude_lane_ids: Option<String>,
-    /// Exclude columns ids filter, comma separated.
+    exclude_lane_ids: Option<IdList>,
+    /// Exclude columns ids filter, comma separated, added to the config ones.
     #[arg(long)]
-    exclude_column_ids: Option<String>,
+    exclude_column_ids: Option<IdList>,
     /// Search by column ids filter, comma separated.
     #[arg(long)]
-    column_ids: Option<String>,
+    column_ids: Option<IdList>,
     /// Search by member ids filter, comma separated.
     #[arg(long)]
-    member_ids: Option<String>,
+    member_ids: Option<IdList>,
     /// Search by owner ids filter, comma separated.
     #[arg(long)]
-    owner_ids: Option<String>,
+    owner_ids: Option<IdList>,
     /// Search by responsible ids filter, comma separated.
     #[arg(long)]
-    responsible_ids: Option<String>,
-    /// Maximum amount of cards in response.
+    responsible_ids: Option<IdList>,
+    /// Maximum amount of cards in response, fetches a single page instead of all cards.
     #[arg(long)]
     limit: Option<u32>,
     /// Number of records to skip.
     #[arg(long)]
     offset: Option<u32>,
+    /// Fetch all pages of cards, the default when --limit is not given.
+    #[arg(long, conflicts_with = "limit")]
+    all: bool,
+    /// Stop after N cards, counted after the properties filters.
+    #[arg(long, value_name = "N")]
+    max: Option<usize>,
     /// Order by space id.
     #[arg(long)]
     order_space_id: Option<u32>,
-    /// Filter by column id.
-    #[arg(long)]
-    column_id: Option<u32>,
-    /// Filter by lane id.
-    #[arg(long)]
-    lane_id: Option<u32>,
+    /// Filter by column id or name, `Parent/Child` for subcolumns, names are looked up on the board.
+    #[arg(long, visible_alias = "column-id")]
+    column: Option<String>,
+    /// Filter by lane id or name, names are looked up on the board.
+    #[arg(long, visible_alias = "lane-id")]
+    lane: Option<String>,
     /// Filter by type id.
     #[arg(long)]
     type_id: Option<u32>,
@@ -176,140 +285,141 @@ pub struct Ls {
     /// Filter by due date is set.
     #[arg(long)]
     with_due_date: Option<bool>,
+    /// Show due date column.
+    #[arg(long)]
+    show_due_date: bool,
+    /// Start from the filters saved with --save, the other flags are added on top.
+    #[arg(long, value_name = "NAME")]
+    saved: Option<String>,
+    /// Save the filters of this command under NAME in the config, without --limit and --offset.
+    #[arg(long, value_name = "NAME")]
+    save: Option<String>,
 }
 
 impl Ls {
-    pub fn get_url(&self) -> String {
-        let mut url = String::from("cards?");
-        let config = CONFIG.lock().unwrap();
-        if let Some(board_id) = config.get_board_id() {
-            url.push_str(&format!("board_id={}&", board_id));
-        }
-        url.push_str(&format!("condition={}&", self.condition));
-        if !self.states.is_empty() {
-            url.push_str(&format!("states={}&", self.states));
-        }
-        if let Some(created_before) = &self.created_before {
-            url.push_str(&format!("created_before={}&", created_before));
-        }
-        if let Some(created_after) = &self.created_after {
-            url.push_str(&format!("created_after={}&", created_after));
-        }
-        if let Some(updated_before) = &self.updated_before {
-            url.push_str(&format!("updated_before={}&", updated_before));
-        }
-        if let Some(updated_after) = &self.updated_after {
-            url.push_str(&format!("updated_after={}&", updated_after));
-        }
-        if let Some(first_moved_in_progress_after) = &self.first_moved_in_progress_after {
-            url.push_str(&format!(
-                "first_moved_in_progress_after={}&",
-                first_moved_in_progress_after
-            ));
-        }
-        if let Some(first_moved_in_progress_before) = &self.first_moved_in_progress_before {
-            url.push_str(&format!(
-                "first_moved_in_progress_before={}&",
-                first_moved_in_progress_before
-            ));
-        }
-        if let Some(last_moved_to_done_at_after) = &self.last_moved_to_done_at_after {
-            url.push_str(&format!(
-                "last_moved_to_done_at_after={}&",
-                last_moved_to_done_at_after
-            ));
-        }
-        if let Some(last_moved_to_done_at_before) = &self.last_moved_to_done_at_before {
-            url.push_str(&format!(
-                "last_moved_to_done_at_before={}&",
-                last_moved_to_done_at_before
-            ));
-        }
-        if let Some(due_date_after) = &self.due_date_after {
-            url.push_str(&format!("due_date_after={}&", due_date_after));
-        }
-        if let Some(due_date_before) = &self.due_date_before {
-            url.push_str(&format!("due_date_before={}&", due_date_before));
-        }
-        if let Some(query) = &self.query {
-            url.push_str(&format!("query={}&", query));
-        }
-        if let Some(tag) = &self.tag {
-            url.push_str(&format!("tag={}&", tag));
-        }
-        if let Some(tag_ids) = &self.tag_ids {
-            url.push_str(&format!("tag_ids={}&", tag_ids));
-        }
-        if let Some(type_ids) = &self.type_ids {
-            url.push_str(&format!("type_ids={}&", type_ids));
-        }
-        if let Some(exclude_board_ids) = config.get_exclude_board_ids() {
-            url.push_str(&format!("exclude_board_ids={}&", exclude_board_ids));
-        }
-        if let Some(exclude_lane_ids) = config.get_exclude_lane_ids() {
-            url.push_str(&format!("exclude_lane_ids={}&", exclude_lane_ids));
-        }
-        if let Some(exclude_column_ids) = config.get_exclude_column_ids() {
-            url.push_str(&format!("exclude_column_ids={}&", exclude_column_ids));
-        }
-        if let Some(column_ids) = &self.column_ids {
-            url.push_str(&format!("column_ids={}&", column_ids));
-        }
-        if let Some(member_ids) = &self.member_ids {
-            url.push_str(&format!("member_ids={}&", member_ids));
-        }
-        if let Some(owner_ids) = &self.owner_ids {
-            url.push_str(&format!("owner_ids={}&", owner_ids));
-        }
-        if let Some(responsible_ids) = &self.responsible_ids {
-            url.push_str(&format!("responsible_ids={}&", responsible_ids));
-        }
-        if let Some(space_id) = config.get_space_id() {
-            url.push_str(&format!("space_id={}&", space_id));
-        }
-        if let Some(limit) = self.limit {
-            url.push_str(&format!("limit={}&", limit));
-        }
-        if let Some(offset) = self.offset {
-            url.push_str(&format!("offset={}&", offset));
-        }
-        if let Some(order_space_id) = self.order_space_id {
-            url.push_str(&format!("order_space_id={}&", order_space_id));
-        }
-        if let Some(column_id) = self.column_id {
-            url.push_str(&format!("column_id={}&", column_id));
-        }
-        if let Some(lane_id) = self.lane_id {
-            url.push_str(&format!("lane_id={}&", lane_id));
-        }
-        if let Some(type_id) = self.type_id {
-            url.push_str(&format!("type_id={}&", type_id));
-        }
-        if let Some(responsible_id) = self.responsible_id {
-            url.push_str(&format!("responsible_id={}&", responsible_id));
-        }
-        if let Some(owner_id) = self.owner_id {
-            url.push_str(&format!("owner_id={}&", owner_id));
-        }
-        if let Some(archived) = self.archived {
-            url.push_str(&format!("archived={}&", archived));
-        }
-        if let Some(asap) = self.asap {
-            url.push_str(&format!("asap={}&", asap));
-        }
-        if let Some(overdue) = self.overdue {
-            url.push_str(&format!("overdue={}&", overdue));
-        }
-        if let Some(done_on_time) = self.done_on_time {
-            url.push_str(&format!("done_on_time={}&", done_on_time));
-        }
-        if let Some(with_due_date) = self.with_due_date {
-            url.push_str(&format!("with_due_date={}&", with_due_date));
-        }
-        if url.ends_with('&') {
-            url.pop();
+    /// Filters of the command on top of the defaults, `config` and the `--saved` query
+    fn to_filter(&self, config: &Config, info: Option<&Info>) -> Result<CardFilter, Error> {
+        Ok(CardFilter::not_done()
+            .merge(&config.to_filter())
+            .merge(&self.saved_filter(config)?)
+            .merge(&self.args_filter(config, info)?))
+    }
+
+    fn saved_filter(&self, config: &Config) -> Result<CardFilter, Error> {
+        match &self.saved {
+            Some(name) => config.get_query(name).cloned().ok_or_else(|| {
+                Error::Invalid(format!(
+                    "Saved query `{}` not found, create it with `cards ls --save {}`",
+                    name, name
+                ))
+            }),
+            None => Ok(CardFilter::default()),
         }
-        url
+    }
+
+    /// Filters given on the command line, `None` for the flags which are not set
+    fn args_filter(&self, config: &Config, info: Option<&Info>) -> Result<CardFilter, Error> {
+        let (limit, offset) = if self.fetch_all() {
+            (None, None)
+        } else {
+            (self.limit, self.offset)
+        };
+        let board_id = config.get_board_id();
+        let column_id = resolve_filter_id("column", self.column.as_deref(), info, |info, column| {
+            info.resolve_column(column, board_id).map(|column| column.get_id())
+        })?;
+        let lane_id = resolve_filter_id("lane", self.lane.as_deref(), info, |info, lane| {
+            info.resolve_lane(lane, board_id).map(|lane| lane.get_id())
+        })?;
+        Ok(CardFilter {
+            condition: self.condition,
+            states: self.states.clone(),
+            column_id,
+            lane_id,
+            type_id: self.type_id,
+            responsible_id: self.responsible_id,
+            owner_id: self.owner_id,
+            column_ids: self.column_ids.clone(),
+            tag_ids: self.tag_ids.clone(),
+            type_ids: self.type_ids.clone(),
+            member_ids: self.member_ids.clone(),
+            owner_ids: self.owner_ids.clone(),
+            responsible_ids: self.responsible_ids.clone(),
+            exclude_board_ids: self.exclude_board_ids.clone(),
+            exclude_lane_ids: self.exclude_lane_ids.clone(),
+            exclude_column_ids: self.exclude_column_ids.clone(),
+            query: self.query.clone(),
+            tag: self.tag.clone(),
+            created_before: self.created_before.clone(),
+            created_after: self.created_after.clone(),
+            updated_before: self.updated_before.clone(),
+            updated_after: self.updated_after.clone(),
+            first_moved_in_progress_after: self.first_moved_in_progress_after.clone(),
+            first_moved_in_progress_before: self.first_moved_in_progress_before.clone(),
+            last_moved_to_done_at_after: self.last_moved_to_done_at_after.clone(),
+            last_moved_to_done_at_before: self.last_moved_to_done_at_before.clone(),
+            due_date_after: self.due_date_after.clone(),
+            due_date_before: self.due_date_before.clone(),
+            archived: self.archived,
+            asap: self.asap,
+            overdue: self.overdue,
+            done_on_time: self.done_on_time,
+            with_due_date: self.with_due_date,
+            order_space_id: self.order_space_id,
+            limit,
+            offset,
+            ..CardFilter::default()
+        })
+    }
+
+    /// Store the `--saved` query with the flags of this run in the config file.
+    /// The file is loaded again, so `--space-id`/`--board-id` of this run are not saved
+    fn save_query(&self, name: &str, config: &Config, info: Option<&Info>) -> Result<(), Error> {
+        let filter = self.saved_filter(config)?.merge(&CardFilter {
+            limit: None,
+            offset: None,
+            ..self.args_filter(config, info)?
+        });
+        let mut file_config = Config::load()?.unwrap_or_default();
+        file_config.set_query(name, filter);
+        file_config.save()?;
+        Ok(())
+    }
+
+    /// Page through all cards unless a single page is requested with `--limit`
+    fn fetch_all(&self) -> bool {
+        self.all || self.limit.is_none()
+    }
+
+    /// Filters by custom properties, the API does not support them
+    fn keep(&self, card: &ModelsCard) -> bool {
+        self.properties_id.is_none_or(|p_id| card.is_property(p_id))
+            && self
+                .properties_value_id
+                .is_none_or(|pv_id| card.is_property_value(pv_id))
+    }
+}
+
+/// Id of a `cards ls` column or lane given by id or name.
+/// Numbers which are not found in the cache are sent as they are, the cache may be outdated
+fn resolve_filter_id(
+    kind: &str,
+    value: Option<&str>,
+    info: Option<&Info>,
+    resolve: impl Fn(&Info, &str) -> Result<u32, String>,
+) -> Result<Option<u32>, Error> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    let id = value.trim().parse::<u32>().ok();
+    match (info.map(|info| resolve(info, value)), id) {
+        (Some(Ok(found)), _) => Ok(Some(found)),
+        (_, Some(id)) => Ok(Some(id)),
+        (Some(Err(err)), None) => Err(Error::Invalid(err)),
+        (None, None) => Err(Error::Cache(format!(
+            "The entities cache is needed to find the {} `{}`, or use its id",
+            kind, value
+        ))),
     }
 }
 
@@ -319,124 +429,627 @@ fn validate_iso8601(s: &str) -> Result<String, String> {
 }
 
 impl Card {
-    pub fn get_url(&self) -> String {
+    /// Card which is read before the command is applied
+    pub fn get_card_id(&self) -> Option<u32> {
         match &self.command {
-            CardCommands::Ls(ls) => ls.get_url(),
             CardCommands::Get { card_id }
-            | CardCommands::Edit { card_id, .. }
+            | CardCommands::Edit(Edit { card_id, .. })
             | CardCommands::Mv { card_id, .. }
             | CardCommands::Parents { card_id }
-            | CardCommands::Childrens { card_id } => {
-                format!("cards/{}", card_id)
-            }
-            _ => String::new(),
+            | CardCommands::Childrens { card_id }
+            | CardCommands::Tag { card_id, .. }
+            | CardCommands::Members { card_id, .. }
+            | CardCommands::Tree { card_id, .. } => Some(*card_id),
+            _ => None,
         }
     }
-    pub async fn get_table(&self, client: ApiClient) -> Result<String, Box<dyn std::error::Error>> {
-        let api_url = self.get_url();
-        let response = client.get_data(&api_url).await?;
+    /// Rendered result for main to print, csv/tsv rows of `cards ls` are written to `out` page by page
+    pub async fn get_table(&self, ctx: &Context, out: &mut dyn Write) -> Result<String, Error> {
+        let client = ctx.client()?;
+        let output = ctx.output;
+        match &self.command {
+            CardCommands::New {} => return Self::create_card(ctx).await,
+            CardCommands::Due { card_id, date } => {
+                let due_date = parse_due_date(date, Local::now().date_naive()).map_err(Error::Invalid)?;
+                let patch = CardPatch {
+                    due_date: Some(due_date),
+                    ..Default::default()
+                };
+                client.update_card(*card_id, &patch).await?;
+                return Ok(String::new());
+            }
+            CardCommands::Children {
+                action,
+                parent_id,
+                child_id,
+            } => {
+                let api_url = format!("cards/{}/children", parent_id);
+                match action {
+                    ChildrenAction::Add => {
+                        let child = serde_json::json!({ "card_id": child_id });
+                        let _ = client.post_data(&api_url, child).await?;
+                    }
+                    ChildrenAction::Rm => {
+                        let api_url = format!("{}/{}", api_url, child_id);
+                        let _ = client.delete_data(&api_url).await?;
+                    }
+                }
+                return Ok(String::new());
+            }
+            CardCommands::Ls(ls) if output.is_streamable() => {
+                // the header goes out with the first page, so nothing is written if it fails
+                let mut header = Some(output.header::<ModelsCard>());
+                Self::list_cards(ctx, ls, |page| {
+                    if let Some(header) = header.take() {
+                        writeln!(out, "{}", header)?;
+                    }
+                    if !page.is_empty() {
+                        writeln!(out, "{}", output.rows(page))?;
+                    }
+                    Ok(out.flush()?)
+                })
+                .await?;
+                return Ok(String::new());
+            }
+            CardCommands::Ls(ls) => {
+                let mut cards = Self::list_cards(ctx, ls, |_| Ok(())).await?;
+                cards.sort_by(|a, b| a.sort_order.partial_cmp(&b.sort_order).unwrap());
+                return output.render_list::<_, CardView>(cards, |cards| {
+                    ModelsCard::list_to_table_string(&cards, ls.show_due_date, Local::now().fixed_offset())
+                });
+            }
+            _ => {}
+        }
+        let Some(card_id) = self.get_card_id() else {
+            return Ok(String::new());
+        };
+        let card = client.get_card(card_id).await?;
         let table = match &self.command {
             CardCommands::Get { card_id: _ } => {
-                let card: ModelsCard = response.json().await?;
-                card.to_table_string()
+                output.render_one::<_, CardView>(card, |card| card.to_table_string())?
             }
-            CardCommands::Edit{card_id: _, description, add_description, title, column_id, lane_id} => {
-                let mut card: ModelsCard = response.json().await?;
-                if let Some(desc) = description {
-                    card.set_description(desc.to_owned(), false);
-                };
-                if let Some(add_desc) = add_description {
-                    card.set_description(add_desc.to_owned(), true);
+            CardCommands::Edit(edit) => {
+                if !edit.has_changes() {
+                    return Self::edit_in_editor(ctx, card, edit.check_updated).await;
+                }
+                let mut patch = CardPatch {
+                    title: edit.title.to_owned(),
+                    description: edit.description.to_owned(),
+                    asap: edit.get_asap(),
+                    size_text: edit.size.to_owned(),
+                    ..Default::default()
                 };
-                if let Some(title) = title {
-                    card.set_title(title.to_owned());
+                if let Some(add_desc) = &edit.add_description {
+                    let desc = patch
+                        .description
+                        .take()
+                        .unwrap_or_else(|| card.get_description().to_string());
+                    patch.description = Some(if desc.is_empty() {
+                        add_desc.to_owned()
+                    } else {
+                        format!("{}\n{}", desc, add_desc)
+                    });
                 };
-                if let Some(column_id) = column_id {
-                    card.set_column_id(*column_id);
-                    let info = INFO.get().unwrap();
-                    let board_id = info.get_board_id_by_column_id(*column_id);
-                    if let Some(board_id) = board_id {
-                        card.set_board_id(board_id);
-                    };
+                if edit.column.is_some() || edit.lane.is_some() {
+                    Self::set_location(ctx, &mut patch, &card, edit.column.as_deref(), edit.lane.as_deref())?;
                 };
-                if let Some(lane_id) = lane_id {
-                    card.set_lane_id(*lane_id);
+                if let Some(card_type) = &edit.card_type {
+                    let card_type = ctx.info()?.find_card_type(card_type).ok_or_else(|| {
+                        Error::Invalid(format!(
+                            "Card type `{}` not found. Use `kaiten-cli types` to list them",
+                            card_type
+                        ))
+                    })?;
+                    patch.type_id = Some(card_type.get_id());
                 };
-                let _ = client.patch_data(&api_url, card).await?;
-                String::new()
-            }
-            CardCommands::Ls(ls) => {
-                let mut cards: Vec<ModelsCard> = response.json().await?;
-                if let Some(p_id) = ls.properties_id {
-                    cards = cards.into_iter().filter(|c| c.is_property(p_id)).collect()
-                }
-                if let Some(pv_id) = ls.properties_value_id {
-                    cards = cards
-                        .into_iter()
-                        .filter(|c| c.is_property_value(pv_id))
-                        .collect()
+                if edit.check_updated {
+                    Self::check_not_updated(client, &card).await?;
                 }
-                cards.sort_by(|a, b| a.sort_order.partial_cmp(&b.sort_order).unwrap());
-
-                Table::new(cards)
-                    .modify(Columns::first(), Width::increase(10))
-                    .modify(Columns::single(1), Width::wrap(70).keep_words())
-                    .modify(Columns::single(2), Width::increase(10))
-                    .with(Style::modern())
-                    .with(Width::wrap(135).keep_words())
-                    .to_string()
+                client.update_card(card_id, &patch).await?;
+                String::new()
             }
             CardCommands::Parents { card_id: _ } => {
-                let card: ModelsCard = response.json().await?;
                 Table::new(card.get_parents())
                     .modify(Columns::single(1), Width::wrap(80).keep_words())
                     .with(Style::modern())
                     .to_string()
             }
             CardCommands::Childrens { card_id: _ } => {
-                let card: ModelsCard = response.json().await?;
                 Table::new(card.get_childrens())
                     .modify(Columns::single(1), Width::wrap(80).keep_words())
                     .with(Style::modern())
                     .to_string()
             }
 
-            CardCommands::New {} => ModelsCard::from_string(),
-
             CardCommands::Mv {
                 card_id: _,
-                column_id,
-                lane_id,
+                column,
+                lane,
                 add_responsible,
+                check_updated,
             } => {
-                let mut card: ModelsCard = response.json().await?;
-                let info = INFO.get().unwrap();
-                card.set_column_id(*column_id);
-                let board_id = info.get_board_id_by_column_id(*column_id);
-                card.set_lane_id(*lane_id);
-                if let Some(board_id) = board_id {
-                    card.set_board_id(board_id);
-                } else {
-                    let err: Box<dyn std::error::Error> =
-                        format!("Not found board_id for column_id: {}", column_id).into();
-                    return Err(err);
-                }
-                let api_url = self.get_url();
-                let _ = client.patch_data(&api_url, card).await?;
-                if let Some(username) = add_responsible {
-                    let user = info.get_user(&username, None);
-                    if let Some(mut user) = user {
-                        user.set_responsible();
-                        let api_url = format!("{}/members", api_url);
-                        let _ = client.post_data(&api_url, &user).await?;
-                        let api_url = format!("{}/{}", api_url, user.get_id());
-                        let _ = client.patch_data(&api_url, user).await?;
+                let responsible = match add_responsible {
+                    Some(username) => {
+                        Self::resolve_users(ctx.info()?, &[username.to_owned()], None)?.pop()
                     }
+                    None => None,
+                };
+                let is_member = responsible
+                    .as_ref()
+                    .is_some_and(|user| card.is_member(user.get_username()));
+                let mut patch = CardPatch::default();
+                Self::set_location(ctx, &mut patch, &card, Some(column), lane.as_deref())?;
+                if *check_updated {
+                    Self::check_not_updated(client, &card).await?;
+                }
+                client.update_card(card_id, &patch).await?;
+                if let Some(user) = responsible {
+                    let card_url = format!("cards/{}", card_id);
+                    Self::add_responsible(client, &card_url, user, is_member).await?;
                 };
                 String::from("")
             }
-            _ => String::from(""),
+            CardCommands::Tag { card_id: _, tags } => {
+                Self::edit_tags(ctx, &card, tags).await?
+            }
+            CardCommands::Members {
+                card_id: _,
+                action,
+                usernames,
+            } => {
+                Self::edit_members(ctx, &card, action, usernames).await?
+            }
+            CardCommands::Tree { card_id, depth } => {
+                Self::card_tree(client, card, *depth).await?
+                    .map(|tree| tree.render(ctx.try_info()))
+                    .ok_or_else(|| Error::NotFound(format!("Card {} not found", card_id)))?
+            }
+            CardCommands::New {}
+            | CardCommands::Children { .. }
+            | CardCommands::Due { .. }
+            | CardCommands::Ls(_) => String::new(),
         };
         Ok(table)
     }
+
+    async fn edit_tags(
+        ctx: &Context,
+        card: &ModelsCard,
+        tags: &[String],
+    ) -> Result<String, Error> {
+        let client = ctx.client()?;
+        let info = ctx.info()?;
+        let tags_url = format!("cards/{}/tags", card.get_id());
+        let card_tags = card.get_tags();
+        let mut result = Vec::new();
+        for tag in tags {
+            if let Some(name) = tag.strip_prefix('-') {
+                let found = card_tags
+                    .iter()
+                    .find(|t| eq_ignore_case(t.get_name(), name.trim()));
+                match found {
+                    Some(card_tag) => {
+                        let api_url = format!("{}/{}", tags_url, card_tag.get_id());
+                        let _ = client.delete_data(&api_url).await?;
+                        result.push(format!("Removed tag `{}`", card_tag.get_name()));
+                    }
+                    None => result.push(format!("Card has no tag `{}`", name)),
+                }
+                continue;
+            }
+            let name = tag.strip_prefix('+').unwrap_or(tag).trim();
+            if card_tags.iter().any(|t| eq_ignore_case(t.get_name(), name)) {
+                result.push(format!("Card already has tag `{}`", name));
+                continue;
+            }
+            let name = match info.find_tag(name) {
+                Some(known) => known.get_name().to_string(),
+                None => {
+                    let question = format!("Tag `{}` does not exist. Create it?", name);
+                    if !confirm(&question)? {
+                        result.push(format!("Skipped unknown tag `{}`", name));
+                        continue;
+                    }
+                    name.to_string()
+                }
+            };
+            let _ = client
+                .post_data(&tags_url, serde_json::json!({ "name": name }))
+                .await?;
+            result.push(format!("Added tag `{}`", name));
+        }
+        Ok(result.join("\n"))
+    }
+
+    /// Fetch cards matching `ls`, page by page with concurrent requests when all cards are requested.
+    /// `on_page` gets the filtered cards of every page in the API order as soon as it arrives
+    async fn list_cards(
+        ctx: &Context,
+        ls: &Ls,
+        mut on_page: impl FnMut(&[ModelsCard]) -> Result<(), Error>,
+    ) -> Result<Vec<ModelsCard>, Error> {
+        let client = ctx.client()?;
+        let filter = ls.to_filter(&ctx.config, ctx.try_info())?;
+        if let Some(name) = &ls.save {
+            ls.save_query(name, &ctx.config, ctx.try_info())?;
+        }
+        let mut cards: Vec<ModelsCard> = Vec::new();
+        if !ls.fetch_all() {
+            let mut page = client.list_cards(&filter).await?;
+            page.retain(|card| ls.keep(card));
+            if let Some(max) = ls.max {
+                page.truncate(max);
+            }
+            on_page(&page)?;
+            return Ok(page);
+        }
+        let mut pages = client.card_pages(&filter);
+        while let Some(page) = pages.try_next().await? {
+            let mut page: Vec<ModelsCard> = page.into_iter().filter(|card| ls.keep(card)).collect();
+            if let Some(max) = ls.max {
+                page.truncate(max.saturating_sub(cards.len()));
+            }
+            on_page(&page)?;
+            cards.extend(page);
+            if ls.max.is_some_and(|max| cards.len() >= max) {
+                break;
+            }
+        }
+        Ok(cards)
+    }
+
+    /// Fetch card descendants level by level with concurrent requests
+    async fn card_tree(
+        client: &ApiClient,
+        root: ModelsCard,
+        depth: u32,
+    ) -> Result<Option<CardTree>, Error> {
+        const CONCURRENT_REQUESTS: usize = 8;
+        let root_id = root.get_id();
+        let mut cards: HashMap<u32, ModelsCard> = HashMap::from([(root_id, root)]);
+        let mut level = vec![root_id];
+        for _ in 0..depth {
+            let mut ids: Vec<u32> = level
+                .iter()
+                .flat_map(|id| cards[id].get_childrens())
+                .map(|child| child.get_id())
+                .filter(|id| !cards.contains_key(id))
+                .collect();
+            ids.sort_unstable();
+            ids.dedup();
+            if ids.is_empty() {
+                break;
+            }
+            let children: Vec<ModelsCard> = stream::iter(ids.iter())
+                .map(|id| client.get_card(*id))
+                .buffer_unordered(CONCURRENT_REQUESTS)
+                .try_collect()
+                .await?;
+            cards.extend(children.into_iter().map(|c| (c.get_id(), c)));
+            level = ids;
+        }
+        Ok(CardTree::build(root_id, &mut cards))
+    }
+
+    /// Resolve all usernames or fail with the list of unknown ones
+    fn resolve_users(
+        info: &Info,
+        usernames: &[String],
+        space_id: Option<u32>,
+    ) -> Result<Vec<User>, Error> {
+        let mut users = Vec::new();
+        let mut unknown = Vec::new();
+        for username in usernames {
+            match info.get_user(username, space_id) {
+                Some(user) => users.push(user),
+                None => unknown.push(username.as_str()),
+            }
+        }
+        if !unknown.is_empty() {
+            return Err(Error::Invalid(format!(
+                "Unknown username: {}. Use `kaiten-cli users` to list them",
+                unknown.join(", ")
+            )));
+        }
+        Ok(users)
+    }
+
+    async fn edit_members(
+        ctx: &Context,
+        card: &ModelsCard,
+        action: &MembersAction,
+        usernames: &[String],
+    ) -> Result<String, Error> {
+        let client = ctx.client()?;
+        let info = ctx.info()?;
+        let space_id = info.get_space_id_by_board_id(card.get_board_id());
+        let users = Self::resolve_users(info, usernames, space_id)?;
+        let card_url = format!("cards/{}", card.get_id());
+        let members_url = format!("{}/members", card_url);
+        let mut result = Vec::new();
+        match action {
+            MembersAction::Add => {
+                for user in users {
+                    if card.is_member(user.get_username()) {
+                        result.push(format!("`{}` is already a member", user));
+                        continue;
+                    }
+                    let _ = client.post_data(&members_url, &user).await?;
+                    result.push(format!("Added `{}`", user));
+                }
+            }
+            MembersAction::Rm => {
+                for user in users {
+                    if !card.is_member(user.get_username()) {
+                        result.push(format!("`{}` is not a member", user));
+                        continue;
+                    }
+                    let api_url = format!("{}/{}", members_url, user.get_id());
+                    let _ = client.delete_data(&api_url).await?;
+                    result.push(format!("Removed `{}`", user));
+                }
+            }
+            MembersAction::Responsible => {
+                let [user] = <[User; 1]>::try_from(users)
+                    .map_err(|_| Error::Invalid("Only one responsible user can be set".to_string()))?;
+                let is_member = card.is_member(user.get_username());
+                let username = user.get_username().to_string();
+                Self::add_responsible(client, &card_url, user, is_member).await?;
+                result.push(format!("`{}` is responsible now", username));
+            }
+        }
+        Ok(result.join("\n"))
+    }
+
+    async fn add_responsible(
+        client: &ApiClient,
+        card_url: &str,
+        mut user: User,
+        is_member: bool,
+    ) -> Result<(), Error> {
+        user.set_responsible();
+        let api_url = format!("{}/members", card_url);
+        if !is_member {
+            let _ = client.post_data(&api_url, &user).await?;
+        }
+        let api_url = format!("{}/{}", api_url, user.get_id());
+        let _ = client.patch_data(&api_url, user).await?;
+        Ok(())
+    }
+
+    /// Resolve column and lane ids or names into the patch moving the card there.
+    /// Names are looked up on the current board, the card's board if none is set
+    fn set_location(
+        ctx: &Context,
+        patch: &mut CardPatch,
+        card: &ModelsCard,
+        column: Option<&str>,
+        lane: Option<&str>,
+    ) -> Result<(), Error> {
+        let info = ctx.info()?;
+        let current_board_id = ctx.config.get_board_id().unwrap_or(card.get_board_id());
+        let board_id = match column {
+            Some(column) => {
+                let column = info
+                    .resolve_column(column, Some(current_board_id))
+                    .map_err(Error::Invalid)?;
+                let board_id = info
+                    .get_board_id_by_column_id(column.get_id())
+                    .ok_or_else(|| {
+                        Error::Cache(format!("Not found board_id for column_id: {}", column.get_id()))
+                    })?;
+                patch.column_id = Some(column.get_id());
+                board_id
+            }
+            None => card.get_board_id(),
+        };
+        let lane = match lane {
+            Some(lane) => {
+                let lane = info.resolve_lane(lane, Some(board_id)).map_err(Error::Invalid)?;
+                if lane.get_board_id() != board_id {
+                    return Err(Error::Invalid(format!(
+                        "Lane {} is not on board {}",
+                        lane.get_id(),
+                        board_id
+                    )));
+                }
+                Some(lane)
+            }
+            None if board_id != card.get_board_id() => Some(
+                info.get_board(board_id)
+                    .and_then(|b| b.first_lane())
+                    .ok_or_else(|| Error::Cache(format!("Board {} has no lanes", board_id)))?,
+            ),
+            None => None,
+        };
+        if let Some(lane) = lane {
+            patch.lane_id = Some(lane.get_id());
+        }
+        if board_id != card.get_board_id() {
+            patch.board_id = Some(board_id);
+        }
+        Ok(())
+    }
+
+    /// Optimistic concurrency check: re-read the card and compare its `updated` timestamp
+    async fn check_not_updated(
+        client: &ApiClient,
+        card: &ModelsCard,
+    ) -> Result<(), Error> {
+        let current = client.get_card(card.get_id()).await?;
+        // equal empty timestamps would let every change through
+        if card.get_updated().is_empty() || current.get_updated().is_empty() {
+            return Err(Error::Parse(format!(
+                "Card {} has no `updated` timestamp in the API response, it can't be checked for changes, nothing was saved. Run without `--check-updated`",
+                card.get_id()
+            )));
+        }
+        if current.get_updated() != card.get_updated() {
+            return Err(Error::Api(format!(
+                "Card {} was changed at {} after it was read, nothing was saved. Re-run the command to apply changes to the latest version",
+                card.get_id(),
+                current.get_updated()
+            )));
+        }
+        Ok(())
+    }
+
+    async fn edit_in_editor(
+        ctx: &Context,
+        card: ModelsCard,
+        check_updated: bool,
+    ) -> Result<String, Error> {
+        let client = ctx.client()?;
+        let info = ctx.info()?;
+        let card_url = format!("cards/{}", card.get_id());
+        let card_type = card.get_type().get_letter();
+        let template = CardEditTemplate {
+            fields: CardFields {
+                title: Some(card.get_title().to_string()),
+                board: info
+                    .get_board(card.get_board_id())
+                    .map(|b| b.get_title().to_string()),
+                column: info
+                    .get_board(card.get_board_id())
+                    .and_then(|b| b.column_path(card.get_column_id())),
+                lane: Some(card.get_lane().get_title().to_string()),
+                r#type: (!card_type.is_empty()).then(|| card_type.to_string()),
+                due_date: card.get_due_date().map(|d| d.to_string()),
+            },
+            description: card.get_description().to_string(),
+            checklists: Some(card.get_checklists()),
+        };
+        let text = edit_text(&template.to_text()?, ".md")?;
+        let edited = CardEditTemplate::from_text(&text).map_err(Error::Invalid)?;
+        let fields = edited
+            .fields
+            .resolve(info, Some(card.get_board_id()))
+            .map_err(Error::Invalid)?;
+
+        let patch = CardPatch {
+            title: (fields.title != card.get_title()).then_some(fields.title),
+            description: (edited.description != card.get_description().trim())
+                .then_some(edited.description),
+            board_id: Some(fields.board.get_id()).filter(|id| *id != card.get_board_id()),
+            column_id: Some(fields.column.get_id()).filter(|id| *id != card.get_column_id()),
+            lane_id: Some(fields.lane.get_id()).filter(|id| *id != card.get_lane_id()),
+            type_id: fields.type_id.filter(|id| *id != card.get_type().get_id()),
+            due_date: (fields.due_date.as_deref() != card.get_due_date()).then_some(fields.due_date),
+            ..Default::default()
+        };
+        let checklist_changes = match edited.checklists {
+            Some(checklists) => Checklist::diff(&card.get_checklists(), &checklists),
+            None => Vec::new(),
+        };
+        if check_updated && !(patch.is_empty() && checklist_changes.is_empty()) {
+            Self::check_not_updated(client, &card).await?;
+        }
+        let mut result = Vec::new();
+        if !patch.is_empty() {
+            let changed = patch.changed_fields();
+            client.update_card(card.get_id(), &patch).await?;
+            result.push(format!("Updated {}", changed.join(", ")));
+        }
+        if !checklist_changes.is_empty() {
+            let count = checklist_changes.len();
+            Self::apply_checklist_changes(client, &card_url, checklist_changes).await?;
+            result.push(format!("Applied {} checklist changes", count));
+        }
+        if result.is_empty() {
+            Ok(String::from("No changes"))
+        } else {
+            Ok(result.join("\n"))
+        }
+    }
+
+    async fn apply_checklist_changes(
+        client: &ApiClient,
+        card_url: &str,
+        changes: Vec<ChecklistChange>,
+    ) -> Result<(), Error> {
+        let checklists_url = format!("{}/checklists", card_url);
+        for change in changes {
+            match change {
+                ChecklistChange::Create { name, items } => {
+                    let response = client
+                        .post_data(&checklists_url, serde_json::json!({ "name": name }))
+                        .await?;
+                    let checklist: serde_json::Value = response.json().await?;
+                    let checklist_id = checklist["id"]
+                        .as_u64()
+                        .ok_or_else(|| {
+                            Error::Parse("Checklist was created, but the response has no id".to_string())
+                        })?;
+                    let api_url = format!("{}/{}/items", checklists_url, checklist_id);
+                    for item in items {
+                        let _ = client.post_data(&api_url, item).await?;
+                    }
+                }
+                ChecklistChange::Rename { id, name } => {
+                    let api_url = format!("{}/{}", checklists_url, id);
+                    let _ = client
+                        .patch_data(&api_url, serde_json::json!({ "name": name }))
+                        .await?;
+                }
+                ChecklistChange::Delete { id } => {
+                    let api_url = format!("{}/{}", checklists_url, id);
+                    let _ = client.delete_data(&api_url).await?;
+                }
+                ChecklistChange::CreateItem { checklist_id, item } => {
+                    let api_url = format!("{}/{}/items", checklists_url, checklist_id);
+                    let _ = client.post_data(&api_url, item).await?;
+                }
+                ChecklistChange::UpdateItem { checklist_id, item } => {
+                    let api_url = format!("{}/{}/items/{}", checklists_url, checklist_id, item.id);
+                    let _ = client.patch_data(&api_url, item).await?;
+                }
+                ChecklistChange::DeleteItem {
+                    checklist_id,
+                    item_id,
+                } => {
+                    let api_url = format!("{}/{}/items/{}", checklists_url, checklist_id, item_id);
+                    let _ = client.delete_data(&api_url).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn create_card(ctx: &Context) -> Result<String, Error> {
+        let client = ctx.client()?;
+        let info = ctx.info()?;
+        let board_id = ctx.config.get_board_id();
+        let board = board_id.and_then(|board_id| info.get_board(board_id));
+        let template = CardTemplate::new(board.as_ref());
+        let text = edit_text(&template.to_text()?, ".md")?;
+        let template = CardTemplate::from_text(&text).map_err(Error::Invalid)?;
+        let resolved = template.resolve(info, board_id).map_err(Error::Invalid)?;
+
+        let response = client.post_data("cards", &resolved.card).await?;
+        let created: serde_json::Value = response.json().await?;
+        let card_id = created["id"]
+            .as_u64()
+            .ok_or_else(|| Error::Parse("Card was created, but the response has no id".to_string()))?;
+        let card_url = format!("cards/{}", card_id);
+        // the card exists from here on, a failure must not hide its id
+        let details = async {
+            for tag in resolved.tags.iter() {
+                let api_url = format!("{}/tags", card_url);
+                let _ = client
+                    .post_data(&api_url, serde_json::json!({ "name": tag }))
+                    .await?;
+            }
+            if let Some(user) = resolved.responsible {
+                Self::add_responsible(client, &card_url, user, false).await?;
+            }
+            Ok::<(), Error>(())
+        };
+        details.await.map_err(|err| {
+            err.context(&format!(
+                "Created card {}, but failed to set its tags or responsible, retry with `kaiten-cli cards tag {} ...` or `kaiten-cli cards members {} responsible ...`",
+                card_id, card_id, card_id
+            ))
+        })?;
+        Ok(format!("Created card {}", card_id))
+    }
 }
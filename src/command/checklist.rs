@@ -0,0 +1,154 @@
+use super::context::Context;
+use kaiten_cli::error::Error;
+use kaiten_cli::models::common::eq_ignore_case;
+use kaiten_cli::models::{ChecklistItem, ChecklistRow, ItemRef};
+use clap::{Args, Subcommand};
+use tabled::{
+    settings::{object::Columns, Style, Width},
+    Table,
+};
+
+#[derive(Args)]
+pub struct Checklist {
+    #[command(subcommand)]
+    pub command: ChecklistCommands,
+}
+
+#[derive(Subcommand)]
+pub enum ChecklistCommands {
+    /// get card checklists with numbered items
+    Ls { card_id: u32 },
+    /// create new checklist in card
+    Add { card_id: u32, name: String },
+    /// manage checklist items
+    #[command(subcommand)]
+    Item(ItemCommands),
+}
+
+#[derive(Subcommand)]
+pub enum ItemCommands {
+    /// add item to the checklist
+    Add {
+        card_id: u32,
+        /// Checklist name or id
+        checklist: String,
+        text: String,
+    },
+    /// mark item as done
+    Check {
+        card_id: u32,
+        #[command(flatten)]
+        item: ItemArgs,
+    },
+    /// mark item as not done
+    Uncheck {
+        card_id: u32,
+        #[command(flatten)]
+        item: ItemArgs,
+    },
+    /// remove item from the checklist
+    Rm {
+        card_id: u32,
+        #[command(flatten)]
+        item: ItemArgs,
+    },
+}
+
+#[derive(Args)]
+pub struct ItemArgs {
+    /// Item # from `checklists ls`, or the item id with `--id`
+    item: u32,
+    /// ITEM is the item id instead of its # in `checklists ls`
+    #[arg(long)]
+    id: bool,
+}
+
+impl ItemArgs {
+    fn to_ref(&self) -> ItemRef {
+        if self.id {
+            ItemRef::Id(self.item)
+        } else {
+            ItemRef::Index(self.item as usize)
+        }
+    }
+}
+
+impl Checklist {
+    pub fn get_card_id(&self) -> u32 {
+        match &self.command {
+            ChecklistCommands::Ls { card_id }
+            | ChecklistCommands::Add { card_id, .. }
+            | ChecklistCommands::Item(ItemCommands::Add { card_id, .. })
+            | ChecklistCommands::Item(ItemCommands::Check { card_id, .. })
+            | ChecklistCommands::Item(ItemCommands::Uncheck { card_id, .. })
+            | ChecklistCommands::Item(ItemCommands::Rm { card_id, .. }) => *card_id,
+        }
+    }
+    pub fn get_url(&self) -> String {
+        format!("cards/{}/checklists", self.get_card_id())
+    }
+    pub async fn get_table(&self, ctx: &Context) -> Result<String, Error> {
+        let client = ctx.client()?;
+        let api_url = self.get_url();
+        if let ChecklistCommands::Add { card_id: _, name } = &self.command {
+            let _ = client
+                .post_data(&api_url, serde_json::json!({ "name": name }))
+                .await?;
+            return Ok(String::new());
+        }
+        let card = client.get_card(self.get_card_id()).await?;
+        let checklists = card.get_checklists();
+        let rows = ChecklistRow::from_checklists(&checklists);
+        let table = match &self.command {
+            ChecklistCommands::Ls { .. } => Table::new(rows)
+                .modify(Columns::single(2), Width::wrap(80).keep_words())
+                .with(Style::modern())
+                .to_string(),
+            ChecklistCommands::Item(ItemCommands::Add {
+                card_id: _,
+                checklist,
+                text,
+            }) => {
+                let found = checklists.iter().find(|c| {
+                    c.id.is_some_and(|id| id.to_string() == *checklist)
+                        || eq_ignore_case(&c.name, checklist.trim())
+                });
+                let checklist_id = found
+                    .and_then(|c| c.id)
+                    .ok_or_else(|| Error::Invalid(format!("Checklist `{}` not found in card", checklist)))?;
+                let api_url = format!("{}/{}/items", api_url, checklist_id);
+                let item = ChecklistItem::new(text.to_owned(), false);
+                let _ = client.post_data(&api_url, item).await?;
+                String::new()
+            }
+            ChecklistCommands::Item(ItemCommands::Check { card_id: _, item })
+            | ChecklistCommands::Item(ItemCommands::Uncheck { card_id: _, item }) => {
+                let checked = matches!(self.command, ChecklistCommands::Item(ItemCommands::Check { .. }));
+                let row = ChecklistRow::find(rows, item.to_ref()).ok_or_else(|| {
+                    Error::Invalid(format!(
+                        "Checklist item {} not found in card, see `kaiten-cli checklists ls`",
+                        item.to_ref()
+                    ))
+                })?;
+                let api_url = format!("{}/{}/items/{}", api_url, row.checklist_id, row.id);
+                let _ = client
+                    .patch_data(&api_url, serde_json::json!({ "checked": checked }))
+                    .await?;
+                String::new()
+            }
+            ChecklistCommands::Item(ItemCommands::Rm { card_id: _, item }) => {
+                let row = ChecklistRow::find(rows, item.to_ref()).ok_or_else(|| {
+                    Error::Invalid(format!(
+                        "Checklist item {} not found in card, see `kaiten-cli checklists ls`",
+                        item.to_ref()
+                    ))
+                })?;
+                let api_url = format!("{}/{}/items/{}", api_url, row.checklist_id, row.id);
+                let _ = client.delete_data(&api_url).await?;
+                String::new()
+            }
+            ChecklistCommands::Add { .. } => String::new(),
+        };
+        Ok(table)
+    }
+}
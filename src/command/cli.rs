@@ -1,14 +1,23 @@
 use super::card::Card;
 use super::comment::Comment;
-use super::Link;
-use super::{Config, Init};
-use crate::api::ApiClient;
-use crate::models::common::{CONFIG, INFO};
-use crate::models::{Config as ModelsConfig, Info};
-use clap::{Parser, Subcommand};
+use super::{Blocker, Checklist, Link};
+use super::context::Context;
+use super::output::OutputFormat;
+use super::auth;
+use super::config::ConfigCommands;
+use super::{Config, Init, Login};
+use kaiten_cli::error::Error;
+use kaiten_cli::models::view::{
+    BoardView, CardTypeView, ColumnView, LaneView, SpaceView, TagView, UserView,
+};
+use kaiten_cli::models::{Config as ModelsConfig, RunOptions};
+use crate::logger;
+use clap::{ArgAction, Parser, Subcommand};
+use std::env;
+use std::io::Write;
+use std::path::PathBuf;
 use tabled::{settings::Style, Table};
 use tabled::settings::{object::Columns as Cols, Width};
-use std::sync::Mutex;
 
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
@@ -20,12 +29,29 @@ pub struct Cli {
     space_id: Option<u32>,
     #[arg(long, short, global = true)]
     board_id: Option<u32>,
-    /// Don't use config values for filters
+    /// Don't use the filters of config.yaml and .kaiten.yaml, env variables and flags still apply
     #[arg(long, short, global = true)]
     ignore_config: bool,
+    /// Output format. `cards ls` sorts the cards by their position on the board,
+    /// except csv and tsv, which are printed page by page in the API order
+    #[arg(long, short, global = true, value_enum, default_value_t)]
+    pub output: OutputFormat,
+    /// Log API requests to stderr: -v status and timing, -vv headers, -vvv bodies
+    #[arg(long, short, global = true, action = ArgAction::Count)]
+    verbose: u8,
+    /// Write the log to the file instead of stderr
+    #[arg(long, global = true, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+    /// Profile from `config profiles ls` to use instead of the current one, or KAITEN_PROFILE
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
+    /// Print tables without colors, same as the NO_COLOR environment variable
+    #[arg(long, global = true)]
+    no_color: bool,
 }
 
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)]
 pub enum Commands {
     Cards(Card),
     Columns {},
@@ -34,72 +60,102 @@ pub enum Commands {
     Lanes {},
     Spaces {},
     Boards {},
+    /// List card types for `cards edit --type`
+    Types {},
     Comments(Comment),
     Links(Link),
+    /// Manage card blockers
+    Blockers(Blocker),
+    /// Manage card checklists
+    Checklists(Checklist),
     /// Download all info for long-term entity
     Init(Init),
     Config(Config),
+    /// Check the domain and token and save them for the commands without API_URL and KT
+    Login(Login),
+    /// Remove the token saved by login
+    Logout {},
+    /// Show the user of the token, the instance and where the token comes from
+    Whoami {},
 }
 
 impl Cli {
-    pub async fn execute(&self) -> Result<String, Box<dyn std::error::Error>> {
-        ModelsConfig::init_global();
-        {
-            let mut config = CONFIG.lock().unwrap();
-            config.update(self.space_id, self.board_id);
-            if self.ignore_config {
-                config.reset();
-            }
+    /// Result for main to print, long listings are written to `out` as they arrive
+    pub async fn execute(&self, out: &mut dyn Write) -> Result<String, Error> {
+        logger::init(self.verbose, self.log_file.as_deref())?;
+        let options = RunOptions {
+            profile: self.profile.clone(),
+            space_id: self.space_id,
+            board_id: self.board_id,
+            ignore_config: self.ignore_config,
+            // the error of a missing profile tells to run these
+            allow_missing_profile: matches!(
+                &self.command,
+                Commands::Login(_)
+                    | Commands::Config(Config {
+                        command: ConfigCommands::Profiles { .. }
+                    })
+            ),
+        };
+        let config = ModelsConfig::layered(&env::current_dir()?, &options)?;
+        if self.no_color || !self.output.is_table() {
+            colored::control::set_override(false);
         }
-        let client = ApiClient::default()?;
+        let ctx = Context::new(config, self.output);
+        let output = ctx.output;
         let result = match &self.command {
-            Commands::Init(init) => init.execute(client).await?,
-            Commands::Config(config) => config.execute().await?,
+            Commands::Init(init) => init.execute(&ctx).await?,
+            Commands::Config(config) => config.execute(&ctx.config).await?,
+            Commands::Login(login) => login.execute(&ctx).await?,
+            Commands::Logout {} => auth::logout(&ctx)?,
+            Commands::Whoami {} => auth::whoami(&ctx).await?,
             Commands::Columns {} => {
-                Info::init_global();
-                let config = CONFIG.lock().unwrap();
-                let columns = INFO.get().unwrap().get_columns(config.get_board_id());
-                Table::new(columns).modify(Cols::last(), Width::wrap(80).keep_words()).with(Style::modern()).to_string()
+                let columns = ctx.info()?.get_columns(ctx.config.get_board_id());
+                output.render_list::<_, ColumnView>(columns, |columns| {
+                    Table::new(columns).modify(Cols::last(), Width::wrap(80).keep_words()).with(Style::modern()).to_string()
+                })?
             }
             Commands::Users {} => {
-                Info::init_global();
-                let config = CONFIG.lock().unwrap();
-                let users = INFO.get().unwrap().get_users(config.get_space_id());
-                Table::new(users).with(Style::modern()).to_string()
+                let users = ctx.info()?.get_users(ctx.config.get_space_id());
+                output.render_list::<_, UserView>(users, |users| {
+                    Table::new(users).with(Style::modern()).to_string()
+                })?
             }
             Commands::Tags {} => {
-                Info::init_global();
-                let tags = INFO.get().unwrap().get_tags();
-                Table::new(tags).with(Style::modern()).to_string()
+                let tags = ctx.info()?.get_tags();
+                output.render_list::<_, TagView>(tags.clone(), |tags| {
+                    Table::new(tags).with(Style::modern()).to_string()
+                })?
             }
             Commands::Lanes {} => {
-                Info::init_global();
-                let config = CONFIG.lock().unwrap();
-                let lanes = INFO.get().unwrap().get_lanes(config.get_board_id());
-                Table::new(lanes).with(Style::modern()).to_string()
+                let lanes = ctx.info()?.get_lanes(ctx.config.get_board_id());
+                output.render_list::<_, LaneView>(lanes, |lanes| {
+                    Table::new(lanes).with(Style::modern()).to_string()
+                })?
             }
             Commands::Spaces {} => {
-                Info::init_global();
-                let spaces = INFO.get().unwrap().get_spaces();
-                Table::new(spaces).with(Style::modern()).to_string()
+                let spaces = ctx.info()?.get_spaces();
+                output.render_list::<_, SpaceView>(spaces, |spaces| {
+                    Table::new(spaces).with(Style::modern()).to_string()
+                })?
             }
             Commands::Boards {} => {
-                Info::init_global();
-                let boards = INFO.get().unwrap().get_boards();
-                Table::new(boards).with(Style::modern()).to_string()
-            }
-            Commands::Cards(card) => {
-                Info::init_global();
-                card.get_table(client).await?
-            }
-            Commands::Comments(comment) => {
-                Info::init_global();
-                comment.get_table(client).await?
+                let boards = ctx.info()?.get_boards();
+                output.render_list::<_, BoardView>(boards, |boards| {
+                    Table::new(boards).with(Style::modern()).to_string()
+                })?
             }
-            Commands::Links(link) => {
-                Info::init_global();
-                link.get_table(client).await?
+            Commands::Types {} => {
+                let card_types = ctx.info()?.get_card_types();
+                output.render_list::<_, CardTypeView>(card_types.clone(), |card_types| {
+                    Table::new(card_types).with(Style::modern()).to_string()
+                })?
             }
+            Commands::Cards(card) => card.get_table(&ctx, out).await?,
+            Commands::Comments(comment) => comment.get_table(&ctx).await?,
+            Commands::Links(link) => link.get_table(&ctx).await?,
+            Commands::Blockers(blocker) => blocker.get_table(&ctx).await?,
+            Commands::Checklists(checklist) => checklist.get_table(&ctx).await?,
         };
         Ok(result)
     }
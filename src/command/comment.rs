@@ -1,10 +1,14 @@
-use crate::models::Comment as ModelsComment;
+use kaiten_cli::models::Comment as ModelsComment;
 use clap::{Args, Subcommand};
 use tabled::{
-    settings::{object::Columns, Modify, Style, Width},
+    settings::{object::Columns, Style, Width},
     Table,
 };
-use crate::api::ApiClient;
+use super::input::{confirm, edit_text};
+use super::context::Context;
+use kaiten_cli::models::view::CommentView;
+use kaiten_cli::api::ApiClient;
+use kaiten_cli::error::Error;
 
 #[derive(Args)]
 pub struct Comment {
@@ -15,11 +19,25 @@ pub struct Comment {
 #[derive(Subcommand)]
 pub enum CommentCommands {
     /// get card comments
-    Get { card_id: String },
-    /// edit comment in card
-    Edit { card_id: String, comment_id: String },
+    Get { card_id: u32 },
+    /// edit comment in card, opens $EDITOR if text is not set
+    Edit {
+        card_id: u32,
+        comment_id: u32,
+        /// New comment text
+        #[arg(long, short)]
+        text: Option<String>,
+    },
     /// create new comment in card
-    New { card_id: String, comment: String },
+    New { card_id: u32, comment: String },
+    /// remove comment from card
+    Rm {
+        card_id: u32,
+        comment_id: u32,
+        /// Don't ask for confirmation
+        #[arg(long, short)]
+        yes: bool,
+    },
 }
 
 impl Comment {
@@ -31,28 +49,75 @@ impl Comment {
             CommentCommands::Edit {
                 card_id,
                 comment_id,
+                ..
+            }
+            | CommentCommands::Rm {
+                card_id,
+                comment_id,
+                ..
             } => format!("cards/{}/comments/{}", card_id, comment_id),
         }
     }
-    pub async fn get_table(
-        &self,
-        client: ApiClient,
-    ) -> Result<String, Box<dyn std::error::Error>> {
+    pub async fn get_table(&self, ctx: &Context) -> Result<String, Error> {
+        let client = ctx.client()?;
+        let output = ctx.output;
         let api_url = self.get_url();
-        let response = client.get_data(&api_url).await?;
         let table = match &self.command {
-            CommentCommands::Get { .. } => {
-                let mut json: Vec<ModelsComment> = response.json().await?;
+            CommentCommands::Get { card_id } => {
+                let mut json = client.get_comments(*card_id).await?;
                 json.sort_by(|a, b| a.created.partial_cmp(&b.created).unwrap());
-                Table::new(json).modify(Columns::first(), Width::wrap(10)).modify(Columns::single(2), Width::wrap(80).keep_words()).with(Style::modern()).to_string()
+                output.render_list::<_, CommentView>(json, |json| {
+                    Table::new(json).modify(Columns::first(), Width::wrap(10)).modify(Columns::single(2), Width::wrap(80).keep_words()).with(Style::modern()).to_string()
+                })?
             },
-            CommentCommands::New{card_id: _, comment} => {
-                let comment = ModelsComment::from_text(comment);
-                let response = client.post_data(&api_url, comment).await?;
-                response.text().await?
+            CommentCommands::New { card_id, comment } => {
+                let comment = client.add_comment(*card_id, comment).await?;
+                output.render_one::<_, CommentView>(comment, |comment| {
+                    Table::new([comment]).modify(Columns::first(), Width::wrap(10)).modify(Columns::single(2), Width::wrap(80).keep_words()).with(Style::modern()).to_string()
+                })?
+            }
+            CommentCommands::Edit { card_id, comment_id, text } => {
+                let text = match text {
+                    Some(text) => text.to_owned(),
+                    None => {
+                        let comment = Self::get_comment(client, *card_id, *comment_id).await?;
+                        edit_text(comment.get_text(), ".md")?
+                    }
+                };
+                let text = text.trim();
+                if text.is_empty() {
+                    return Err(Error::Invalid(
+                        "Comment text is empty, use `kaiten-cli comments rm` to remove it".to_string(),
+                    ));
+                }
+                let comment = ModelsComment::from_text(text);
+                let _ = client.patch_data(&api_url, comment).await?;
+                String::new()
+            }
+            CommentCommands::Rm { card_id, comment_id, yes } => {
+                if !yes {
+                    let comment = Self::get_comment(client, *card_id, *comment_id).await?;
+                    let question = format!("Remove comment \"{}\"?", comment.get_text());
+                    if !confirm(&question)? {
+                        return Ok(String::from("Cancelled"));
+                    }
+                }
+                let _ = client.delete_data(&api_url).await?;
+                String::new()
             }
-            _ => String::new(),
         };
         Ok(table)
     }
+
+    async fn get_comment(
+        client: &ApiClient,
+        card_id: u32,
+        comment_id: u32,
+    ) -> Result<ModelsComment, Error> {
+        let comments = client.get_comments(card_id).await?;
+        comments
+            .into_iter()
+            .find(|c| c.get_id() == comment_id)
+            .ok_or_else(|| Error::NotFound(format!("Comment {} not found in card {}", comment_id, card_id)))
+    }
 }
@@ -1,6 +1,11 @@
 use clap::{Args, Subcommand};
-use crate::models::Config as ModelsConfig;
-use crate::models::common::CONFIG;
+use kaiten_cli::models::profile::validate_profile_name;
+use kaiten_cli::models::{
+    Config as ModelsConfig, CredentialsStore, IdList, Info, Profile, ProfileRow,
+};
+use kaiten_cli::error::Error;
+use std::fs;
+use tabled::{settings::Style, Table};
 
 #[derive(Args)]
 pub struct Config {
@@ -12,10 +17,43 @@ pub struct Config {
 pub enum ConfigCommands {
     /// Create config file
     New(ConfigArgs ),
-    /// Set config key-value
+    /// Set config key-value, in the profile when one is used
     Set(ConfigArgs),
-    /// Show config
-    Show {},
+    /// Show config file, or with `--origin` the effective values and where they come from
+    Show {
+        /// Show each effective value with its source: default, config.yaml, profile,
+        /// .kaiten.yaml, environment variable or flag
+        #[arg(long)]
+        origin: bool,
+    },
+    /// Manage profiles for several Kaiten instances
+    Profiles {
+        #[command(subcommand)]
+        command: ProfileCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ProfileCommands {
+    /// List profiles and show which one is current
+    Ls {},
+    /// Add a profile with its API url, token variable and filters
+    Add {
+        #[arg(value_parser = validate_profile_name)]
+        name: String,
+        /// API url like https://<your_domain>.kaiten.ru/api/latest
+        #[arg(long)]
+        api_url: String,
+        /// Environment variable with the token, KT by default
+        #[arg(long, value_name = "VAR")]
+        token_env: Option<String>,
+        #[command(flatten)]
+        filters: ConfigArgs,
+    },
+    /// Make the profile current, without NAME the top-level config is used again
+    Use { name: Option<String> },
+    /// Remove the profile and its entities cache
+    Rm { name: String },
 }
 
 #[derive(Args)]
@@ -28,38 +66,73 @@ pub struct ConfigArgs {
     board_id: Option<u32>,
     /// Exclude board ids filter, comma separated.
     #[arg(long)]
-    exclude_board_ids: Option<String>,
+    exclude_board_ids: Option<IdList>,
     /// Exclude lane ids filter, comma separated.
     #[arg(long)]
-    exclude_lane_ids: Option<String>,
+    exclude_lane_ids: Option<IdList>,
     /// Exclude columns ids filter, comma separated.
     #[arg(long)]
-    exclude_column_ids: Option<String>,
+    exclude_column_ids: Option<IdList>,
+}
+
+impl ConfigArgs {
+    fn apply_to_profile(&self, profile: &mut Profile) {
+        if let Some(space_id) = self.space_id {
+            profile.space_id = Some(space_id);
+        }
+        if let Some(board_id) = self.board_id {
+            profile.board_id = Some(board_id);
+        }
+        if let Some(exclude_board_ids) = &self.exclude_board_ids {
+            profile.exclude_board_ids = Some(exclude_board_ids.clone());
+        }
+        if let Some(exclude_lane_ids) = &self.exclude_lane_ids {
+            profile.exclude_lane_ids = Some(exclude_lane_ids.clone());
+        }
+        if let Some(exclude_column_ids) = &self.exclude_column_ids {
+            profile.exclude_column_ids = Some(exclude_column_ids.clone());
+        }
+    }
 }
 
 impl Config {
-    pub async fn execute(&self) -> Result<String, Box<dyn std::error::Error>> {
+    /// `config` is the effective config of the run,
+    /// `config set` changes its profile instead of the top-level values
+    pub async fn execute(&self, config: &ModelsConfig) -> Result<String, Error> {
+        let profile = config.get_profile_name();
         let result = match &self.command {
             ConfigCommands::New(args) => {
-                Config::set_config_values(args);
+                Config::set_config_values(args, profile)?;
                 String::new()
             }
             ConfigCommands::Set(args) => {
-                Config::set_config_values(args);
+                Config::set_config_values(args, profile)?;
                 String::new()
             }
-            ConfigCommands::Show{} => {
+            ConfigCommands::Show { origin: true } => Table::new(config.origin_rows())
+                .with(Style::modern())
+                .to_string(),
+            ConfigCommands::Show { origin: false } => {
                 let config = ModelsConfig::load()?;
-                let result = serde_yaml::to_string(&config)?;
-                result
+                serde_yaml::to_string(&config)?
 
             }
+            ConfigCommands::Profiles { command } => Config::manage_profiles(command)?,
         };
         Ok(result)
     }
 
-    fn set_config_values(args: &ConfigArgs) {
-        let mut config = CONFIG.lock().unwrap();
+    /// Update the saved config file, without `--space-id`/`--board-id` overrides of this run
+    fn set_config_values(args: &ConfigArgs, profile: Option<&str>) -> Result<(), Error> {
+        let mut config = ModelsConfig::load()?.unwrap_or_else(ModelsConfig::new);
+        if let Some(name) = profile {
+            let profile = config
+                .get_profile_mut(name)
+                .ok_or_else(|| Error::Config(format!("Profile `{}` not found", name)))?;
+            args.apply_to_profile(profile);
+            config.save()?;
+            return Ok(());
+        }
         if let Some(space_id) = args.space_id {
             config.set_space_id(space_id)
         }
@@ -67,14 +140,78 @@ impl Config {
             config.set_board_id(board_id)
         }
         if let Some(exclude_board_ids) = &args.exclude_board_ids {
-            config.set_exclude_board_ids(exclude_board_ids.to_string());
+            config.set_exclude_board_ids(exclude_board_ids.clone());
         }
         if let Some(exclude_lane_ids) = &args.exclude_lane_ids {
-            config.set_exclude_lane_ids(exclude_lane_ids.to_string());
+            config.set_exclude_lane_ids(exclude_lane_ids.clone());
         }
         if let Some(exclude_column_ids) = &args.exclude_column_ids {
-            config.set_exclude_column_ids(exclude_column_ids.to_string());
+            config.set_exclude_column_ids(exclude_column_ids.clone());
+        }
+        config.save()?;
+        Ok(())
+    }
+
+    fn manage_profiles(command: &ProfileCommands) -> Result<String, Error> {
+        let mut config = ModelsConfig::load()?.unwrap_or_else(ModelsConfig::new);
+        let not_found = |name: &str| {
+            Error::Invalid(format!(
+                "Profile `{}` not found, see `kaiten-cli config profiles ls`",
+                name
+            ))
+        };
+        match command {
+            ProfileCommands::Ls {} => {
+                let current = config.get_profile_name();
+                let rows: Vec<ProfileRow> = config
+                    .get_profiles()
+                    .iter()
+                    .map(|(name, profile)| ProfileRow::from(name, profile, current == Some(name)))
+                    .collect();
+                return Ok(Table::new(rows).with(Style::modern()).to_string());
+            }
+            ProfileCommands::Add {
+                name,
+                api_url,
+                token_env,
+                filters,
+            } => {
+                if config.get_profiles().contains_key(name) {
+                    return Err(Error::Invalid(format!(
+                        "Profile `{}` already exists. Use `kaiten-cli --profile {} config set` to change its filters or `kaiten-cli config profiles rm {}`",
+                        name, name, name
+                    )));
+                }
+                let mut profile = Profile {
+                    api_url: api_url.to_owned(),
+                    token_env: token_env.to_owned(),
+                    ..Profile::default()
+                };
+                filters.apply_to_profile(&mut profile);
+                config.add_profile(name, profile);
+            }
+            ProfileCommands::Use { name } => {
+                if let Some(name) = name {
+                    if !config.get_profiles().contains_key(name) {
+                        return Err(not_found(name));
+                    }
+                }
+                config.set_current_profile(name.to_owned());
+            }
+            ProfileCommands::Rm { name } => {
+                config.remove_profile(name).ok_or_else(|| not_found(name))?;
+                let cache = Info::path(Some(name))?;
+                if cache.exists() {
+                    fs::remove_file(cache)?;
+                }
+                // a profile added again later must not pick up the old token
+                let mut credentials = CredentialsStore::load()?;
+                if credentials.remove(Some(name)).is_some() {
+                    credentials.save()?;
+                }
+            }
         }
-        let _ = config.save();
-    } 
+        config.save()?;
+        Ok(String::new())
+    }
 }
@@ -0,0 +1,48 @@
+use super::output::OutputFormat;
+use kaiten_cli::api::ApiClient;
+use kaiten_cli::error::Error;
+use kaiten_cli::models::{Config, Info};
+use once_cell::sync::OnceCell;
+
+/// State of one CLI run passed to the commands.
+/// The client and the entities cache are created on first use,
+/// so commands which don't need them work without `KT` or `kaiten-cli init`
+pub struct Context {
+    pub config: Config,
+    pub output: OutputFormat,
+    client: OnceCell<ApiClient>,
+    info: OnceCell<Info>,
+}
+
+impl Context {
+    pub fn new(config: Config, output: OutputFormat) -> Self {
+        Self {
+            config,
+            output,
+            client: OnceCell::new(),
+            info: OnceCell::new(),
+        }
+    }
+
+    /// Client of the profile in use, or of `API_URL` and `KT` without a profile.
+    /// Both fall back to the credentials saved by `kaiten-cli login`
+    pub fn client(&self) -> Result<&ApiClient, Error> {
+        self.client
+            .get_or_try_init(|| match (self.config.get_profile_name(), self.config.get_profile()) {
+                (Some(name), Some(profile)) => ApiClient::from_profile(name, profile),
+                _ => ApiClient::from_env(),
+            })
+    }
+
+    /// Entities cache downloaded by `kaiten-cli init` for the profile in use
+    pub fn info(&self) -> Result<&Info, Error> {
+        self.info.get_or_try_init(|| {
+            Info::load(self.config.get_profile_name()).map_err(|err| Error::Cache(err.to_string()))
+        })
+    }
+
+    /// Entities cache for optional details like column titles, `None` when it is not downloaded
+    pub fn try_info(&self) -> Option<&Info> {
+        self.info().ok()
+    }
+}
@@ -1,29 +1,11 @@
 use std::collections::HashMap;
-use crate::api::ApiClient;
-use crate::models::Info;
+use super::context::Context;
+use kaiten_cli::error::Error;
+use kaiten_cli::models::Info;
 use clap::Args;
-use crate::models::{User, Board, Space, CardType, Tag};
-use std::path::Path;
-use crate::models::common::INFO;
+use kaiten_cli::models::{User, Board, Space, CardType, Tag};
 
 
-#[derive(Debug)]
-struct FileExistsError{
-    file_name: String
-}
-impl FileExistsError {
-    fn new(file_name: String) -> Self {
-        Self { file_name }
-    }
-}
-
-impl std::error::Error for FileExistsError{} 
-impl std::fmt::Display for FileExistsError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "File {} with entities info already exists. Use `kaiten-cli init --show` to view or `kaiten-cli init --update` for download data from API", self.file_name)
-    }
-}
-
 #[derive(Args)]
 pub struct Init {
     /// Show current entities info from cache file
@@ -35,21 +17,19 @@ pub struct Init {
 }
 
 impl Init {
-    pub async fn execute(&self, client: ApiClient) -> Result<String, Box<dyn std::error::Error>> {
+    pub async fn execute(&self, ctx: &Context) -> Result<String, Error> {
         if self.show {
-            Info::init_global();
-            let result = serde_yaml::to_string(INFO.get().unwrap())?;
+            let result = serde_yaml::to_string(ctx.info()?)?;
             Ok(result)
         } else {
             if !self.update {
-                let file = format!("{}/.config/kaiten-cli/entities.yaml", env!["HOME"]);
-                let file_path = Path::new(file.as_str());
+                let file_path = Info::path(ctx.config.get_profile_name())?;
                 if file_path.exists() {
-                    let error = FileExistsError::new(file);
-                    return Err(Box::new(error))
+                    return Err(Error::Invalid(format!("File {} with entities info already exists. Use `kaiten-cli init --show` to view or `kaiten-cli init --update` for download data from API", file_path.display())));
                 }
 
             }
+            let client = ctx.client()?;
             let api_url = "spaces";
             let mut spaces_vec: Vec<Space> = client.get_data(api_url).await?.json().await?;
             let mut boards: HashMap<u32, Board> = HashMap::new();
@@ -69,7 +49,7 @@ impl Init {
             let api_url = "card-types";
             let card_types: Vec<CardType> = client.get_data(api_url).await?.json().await?;
             let info = Info::from(spaces, boards, tags, card_types);
-            let _ = info.save()?;
+            info.save(ctx.config.get_profile_name())?;
             let info_string = serde_yaml::to_string(&info)?;
             Ok(info_string)
         }
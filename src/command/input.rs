@@ -0,0 +1,49 @@
+use std::env;
+use kaiten_cli::error::Error;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::process::Command;
+use tempfile::Builder;
+
+/// Open `$EDITOR` (or `vi`) on a temp file prefilled with `text` and return the saved content
+pub fn edit_text(text: &str, suffix: &str) -> Result<String, Error> {
+    let editor = env::var("EDITOR").unwrap_or_else(|_| String::from("vi"));
+    let mut tmpfile = Builder::new().suffix(suffix).rand_bytes(5).tempfile()?;
+    tmpfile.write_all(text.as_bytes())?;
+    let path = tmpfile.into_temp_path();
+
+    let status = Command::new(&editor).arg(&path).status()?;
+    if !status.success() {
+        return Err(Error::Io(io::Error::other(format!(
+            "Editor `{}` exited with {}",
+            editor, status
+        ))));
+    }
+    Ok(fs::read_to_string(&path)?)
+}
+
+/// Ask a yes/no question on stderr, anything but `y`/`yes` is a no
+pub fn confirm(question: &str) -> Result<bool, Error> {
+    eprint!("{} [y/N] ", question);
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    let answer = answer.trim().to_lowercase();
+    Ok(answer == "y" || answer == "yes")
+}
+
+/// Ask for a line on stderr and return it trimmed
+pub fn prompt(question: &str) -> Result<String, Error> {
+    eprint!("{}: ", question);
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(answer.trim().to_string())
+}
+
+/// Ask for a secret on the terminal without echoing it
+pub fn prompt_secret(question: &str) -> Result<String, Error> {
+    Ok(rpassword::prompt_password(format!("{}: ", question))?
+        .trim()
+        .to_string())
+}
@@ -1,5 +1,7 @@
-use crate::api::ApiClient;
-use crate::models::Link as ModelsLink;
+use super::context::Context;
+use kaiten_cli::error::Error;
+use kaiten_cli::models::view::LinkView;
+use kaiten_cli::models::Link as ModelsLink;
 use clap::{Args, Subcommand};
 use tabled::{
     settings::{object::Columns, Style, Width},
@@ -14,11 +16,23 @@ pub struct Link {
 
 #[derive(Subcommand)]
 pub enum LinkCommands {
-    /// get card comments
+    /// get card links
     Get { card_id: String },
-    /// edit comment in card
-    Edit { card_id: String, link_id: String },
-    /// create new comment in card
+    /// edit link in card
+    #[command(group(clap::ArgGroup::new("changes").required(true).multiple(true)))]
+    Edit {
+        card_id: String,
+        link_id: String,
+        /// New link url
+        #[arg(long, group = "changes")]
+        url: Option<String>,
+        /// New link description
+        #[arg(long, short, group = "changes")]
+        description: Option<String>,
+    },
+    /// remove link from card
+    Rm { card_id: String, link_id: String },
+    /// create new link in card
     New { card_id: String,
         #[arg(long)]
         link: String,
@@ -36,27 +50,45 @@ impl Link {
             LinkCommands::Edit {
                 card_id,
                 link_id,
-            } => format!("cards/{}/external-links/{}", card_id, link_id),
+                ..
+            }
+            | LinkCommands::Rm { card_id, link_id } => {
+                format!("cards/{}/external-links/{}", card_id, link_id)
+            }
         }
     }
-    pub async fn get_table(&self, client: ApiClient) -> Result<String, Box<dyn std::error::Error>> {
+    pub async fn get_table(&self, ctx: &Context) -> Result<String, Error> {
+        let client = ctx.client()?;
+        let output = ctx.output;
         let api_url = self.get_url();
-        let response = client.get_data(&api_url).await?;
         let table = match &self.command {
             LinkCommands::Get { .. } => {
+                let response = client.get_data(&api_url).await?;
                 let links: Vec<ModelsLink> = response.json().await?;
-                Table::new(links)
-                    .modify(Columns::first(), Width::wrap(10))
-                    .modify(Columns::single(2), Width::wrap(80).keep_words())
-                    .with(Style::modern())
-                    .to_string()
+                output.render_list::<_, LinkView>(links, |links| {
+                    Table::new(links)
+                        .modify(Columns::first(), Width::wrap(10))
+                        .modify(Columns::single(2), Width::wrap(80).keep_words())
+                        .with(Style::modern())
+                        .to_string()
+                })?
             }
             LinkCommands::New {card_id: _, link, description} => {
                 let link = ModelsLink::from(link.to_owned(), description.to_owned());
                 let _ = client.post_data(&api_url, link).await?;
                 String::new()
             }
-            _ => String::new(),
+            LinkCommands::Edit {
+                url, description, ..
+            } => {
+                let link = ModelsLink::patch(url.to_owned(), description.to_owned());
+                let _ = client.patch_data(&api_url, link).await?;
+                String::new()
+            }
+            LinkCommands::Rm { .. } => {
+                let _ = client.delete_data(&api_url).await?;
+                String::new()
+            }
         };
         Ok(table)
     }
@@ -1,12 +1,19 @@
-pub use cli::{Cli, Commands};
-pub use card::{Card, CardCommands};
-pub use comment::{Comment, CommentCommands};
+pub use cli::Cli;
 pub use init::Init;
 pub use config::Config;
 pub use link::Link;
+pub use blocker::Blocker;
+pub use checklist::Checklist;
+pub use auth::Login;
 pub mod cli;
 mod card;
 mod comment;
 mod init;
 mod config;
 mod link;
+mod blocker;
+mod checklist;
+mod auth;
+mod context;
+mod input;
+mod output;
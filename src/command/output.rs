@@ -0,0 +1,138 @@
+use clap::ValueEnum;
+use kaiten_cli::error::Error;
+use serde::Serialize;
+use tabled::Tabled;
+
+/// Format of the command result, set with the global `--output` flag
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable table
+    #[default]
+    Table,
+    /// Stable shape described in README
+    Json,
+    /// Same shape as json
+    Yaml,
+    /// Table columns as comma-separated values, `cards ls` streams them in the API order
+    Csv,
+    /// Table columns as tab-separated values, `cards ls` streams them in the API order
+    Tsv,
+    /// Table columns as a Markdown table
+    Markdown,
+}
+
+impl OutputFormat {
+    pub fn is_table(self) -> bool {
+        self == OutputFormat::Table
+    }
+
+    /// Csv and tsv rows do not depend on each other and can be printed as they arrive
+    pub fn is_streamable(self) -> bool {
+        matches!(self, OutputFormat::Csv | OutputFormat::Tsv)
+    }
+
+    /// Render rows as a table with `table`, as views `V` for json/yaml
+    /// or as the `Tabled` columns for csv/tsv/markdown
+    pub fn render_list<T, V>(
+        self,
+        rows: Vec<T>,
+        table: impl FnOnce(Vec<T>) -> String,
+    ) -> Result<String, Error>
+    where
+        T: Tabled,
+        V: Serialize + for<'a> From<&'a T>,
+    {
+        match self {
+            OutputFormat::Table => Ok(table(rows)),
+            OutputFormat::Json | OutputFormat::Yaml => {
+                let views: Vec<V> = rows.iter().map(V::from).collect();
+                self.serialize(&views)
+            }
+            OutputFormat::Csv | OutputFormat::Tsv | OutputFormat::Markdown => {
+                Ok(self.delimited(&rows))
+            }
+        }
+    }
+
+    /// Same as `render_list`, but json/yaml is a single object instead of an array
+    pub fn render_one<T, V>(
+        self,
+        row: T,
+        table: impl FnOnce(T) -> String,
+    ) -> Result<String, Error>
+    where
+        T: Tabled,
+        V: Serialize + for<'a> From<&'a T>,
+    {
+        match self {
+            OutputFormat::Table => Ok(table(row)),
+            OutputFormat::Json | OutputFormat::Yaml => self.serialize(&V::from(&row)),
+            OutputFormat::Csv | OutputFormat::Tsv | OutputFormat::Markdown => {
+                Ok(self.delimited(&[row]))
+            }
+        }
+    }
+
+    fn serialize<V: Serialize>(self, value: &V) -> Result<String, Error> {
+        let text = match self {
+            OutputFormat::Yaml => serde_yaml::to_string(value)?,
+            _ => serde_json::to_string_pretty(value)?,
+        };
+        Ok(text.trim_end().to_string())
+    }
+
+    fn delimited<T: Tabled>(self, rows: &[T]) -> String {
+        if rows.is_empty() {
+            return self.header::<T>();
+        }
+        format!("{}\n{}", self.header::<T>(), self.rows(rows))
+    }
+
+    /// Header line of csv/tsv/markdown, with the separator line for markdown
+    pub fn header<T: Tabled>(self) -> String {
+        let header = self.line(T::headers().into_iter().map(|h| h.to_string()).collect());
+        if self == OutputFormat::Markdown {
+            format!("{}\n|{}", header, "---|".repeat(T::LENGTH))
+        } else {
+            header
+        }
+    }
+
+    /// Row lines of csv/tsv/markdown without the header
+    pub fn rows<T: Tabled>(self, rows: &[T]) -> String {
+        rows.iter()
+            .map(|row| self.line(row.fields().into_iter().map(|f| f.to_string()).collect()))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    fn line(self, cells: Vec<String>) -> String {
+        match self {
+            OutputFormat::Csv => cells
+                .iter()
+                .map(|cell| csv_escape(cell))
+                .collect::<Vec<String>>()
+                .join(","),
+            OutputFormat::Tsv => cells
+                .iter()
+                .map(|cell| cell.replace(['\t', '\n'], " "))
+                .collect::<Vec<String>>()
+                .join("\t"),
+            _ => {
+                let cells: Vec<String> = cells
+                    .iter()
+                    .map(|cell| cell.replace('|', "\\|").replace('\n', "<br>"))
+                    .collect();
+                format!("| {} |", cells.join(" | "))
+            }
+        }
+    }
+}
+
+fn csv_escape(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
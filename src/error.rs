@@ -0,0 +1,180 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Error of any command, each kind has its own exit code for scripts
+#[derive(Debug)]
+pub enum Error {
+    /// `API_URL` or the token is not set and there is no `kaiten-cli login`
+    Credentials(String),
+    /// An environment variable or a config value is invalid
+    Config(String),
+    /// `config.yaml` or `.kaiten.yaml` can't be read or parsed,
+    /// `message` starts with the path and the line
+    ConfigFile { path: PathBuf, message: String },
+    /// Entities cache file is missing or unreadable
+    Cache(String),
+    /// HTTP 401, the token is invalid or expired
+    Unauthorized(String),
+    /// HTTP 403
+    Forbidden(String),
+    /// HTTP 404
+    NotFound(String),
+    /// HTTP 429, `Retry-After` seconds if the server sent it
+    RateLimited {
+        message: String,
+        retry_after: Option<u64>,
+    },
+    /// HTTP 5xx
+    Server(String),
+    /// Any other unsuccessful HTTP status
+    Api(String),
+    /// Connection failures and timeouts
+    Network(String),
+    /// Invalid user input: unknown names, bad dates, empty texts
+    Invalid(String),
+    /// Unexpected API response or broken local file
+    Parse(String),
+    Io(io::Error),
+}
+
+impl Error {
+    /// Map an unsuccessful HTTP response to the error kind
+    pub fn from_status(status: u16, message: String, retry_after: Option<u64>) -> Self {
+        match status {
+            401 => Error::Unauthorized(message),
+            403 => Error::Forbidden(message),
+            404 => Error::NotFound(message),
+            429 => Error::RateLimited {
+                message,
+                retry_after,
+            },
+            500..=599 => Error::Server(message),
+            _ => Error::Api(message),
+        }
+    }
+
+    /// Same kind of error with `context` before the message
+    pub fn context(self, context: &str) -> Self {
+        let with = |message: String| format!("{}: {}", context, message);
+        match self {
+            Error::Credentials(msg) => Error::Credentials(with(msg)),
+            Error::Config(msg) => Error::Config(with(msg)),
+            Error::ConfigFile { path, message } => Error::ConfigFile {
+                path,
+                message: with(message),
+            },
+            Error::Cache(msg) => Error::Cache(with(msg)),
+            Error::Unauthorized(msg) => Error::Unauthorized(with(msg)),
+            Error::Forbidden(msg) => Error::Forbidden(with(msg)),
+            Error::NotFound(msg) => Error::NotFound(with(msg)),
+            Error::RateLimited {
+                message,
+                retry_after,
+            } => Error::RateLimited {
+                message: with(message),
+                retry_after,
+            },
+            Error::Server(msg) => Error::Server(with(msg)),
+            Error::Api(msg) => Error::Api(with(msg)),
+            Error::Network(msg) => Error::Network(with(msg)),
+            Error::Invalid(msg) => Error::Invalid(with(msg)),
+            Error::Parse(msg) => Error::Parse(with(msg)),
+            Error::Io(err) => Error::Io(io::Error::new(err.kind(), with(err.to_string()))),
+        }
+    }
+
+    /// Process exit code, documented in README
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Io(_) => 1,
+            Error::Credentials(_) | Error::Config(_) | Error::ConfigFile { .. } => 3,
+            Error::Cache(_) => 4,
+            Error::Invalid(_) => 5,
+            Error::Parse(_) => 6,
+            Error::Network(_) => 7,
+            Error::Unauthorized(_) => 10,
+            Error::Forbidden(_) => 11,
+            Error::NotFound(_) => 12,
+            Error::RateLimited { .. } => 13,
+            Error::Server(_) => 14,
+            Error::Api(_) => 15,
+        }
+    }
+
+    /// What the user can do about the error
+    pub fn hint(&self) -> Option<String> {
+        let hint = match self {
+            Error::Credentials(_) => {
+                "Set `API_URL` (e.g. https://<company>.kaiten.ru/api/latest/) and `KT` with your API token, or run `kaiten-cli login`"
+            }
+            Error::Config(_) => "Fix the value in the file or the environment variable named above",
+            Error::ConfigFile { path, .. } => {
+                return Some(format!("Fix or remove {}", path.display()))
+            }
+            Error::Cache(_) => "Run `kaiten-cli init` to download entities info",
+            Error::Unauthorized(_) => "The token is invalid or expired, create a new one in Kaiten profile and set `KT` or run `kaiten-cli login`",
+            Error::Forbidden(_) => "Your user has no access to this entity",
+            Error::NotFound(_) => "Check the id, the entity may be deleted or archived",
+            Error::RateLimited {
+                retry_after: Some(seconds),
+                ..
+            } => return Some(format!("Kaiten rate limit is hit, retry in {} seconds", seconds)),
+            Error::RateLimited { .. } => "Kaiten rate limit is hit, retry later",
+            Error::Server(_) | Error::Network(_) => "Kaiten is unavailable, retry later",
+            _ => return None,
+        };
+        Some(hint.to_string())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Credentials(msg)
+            | Error::Config(msg)
+            | Error::Cache(msg)
+            | Error::Unauthorized(msg)
+            | Error::Forbidden(msg)
+            | Error::NotFound(msg)
+            | Error::RateLimited { message: msg, .. }
+            | Error::Server(msg)
+            | Error::Api(msg)
+            | Error::Network(msg)
+            | Error::Invalid(msg)
+            | Error::Parse(msg)
+            | Error::ConfigFile { message: msg, .. } => write!(f, "{}", msg),
+            Error::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_decode() {
+            Error::Parse(format!("Unexpected API response: {}", err))
+        } else {
+            Error::Network(err.to_string())
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Parse(err.to_string())
+    }
+}
+
+impl From<serde_yaml::Error> for Error {
+    fn from(err: serde_yaml::Error) -> Self {
+        Error::Parse(err.to_string())
+    }
+}
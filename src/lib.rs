@@ -0,0 +1,23 @@
+//! Client for the [Kaiten API](https://developers.kaiten.ru) and its models.
+//! The `kaiten-cli` binary is built on top of this library.
+//!
+//! The library keeps no global state: the entities cache ([`models::Info`]) and the
+//! filters ([`models::Config`]) are loaded by the caller and passed where they are needed,
+//! and [`ApiClient`] can be shared between tasks and threads.
+//!
+//! ```no_run
+//! use kaiten_cli::{ApiClient, Error};
+//!
+//! # async fn run() -> Result<(), Error> {
+//! let client = ApiClient::new("https://company.kaiten.ru/api/latest/", "token")?;
+//! let card = client.get_card(123).await?;
+//! client.add_comment(card.get_id(), "Deployed to staging").await?;
+//! # Ok(())
+//! # }
+//! ```
+pub mod api;
+pub mod error;
+pub mod models;
+
+pub use api::ApiClient;
+pub use error::Error;
@@ -0,0 +1,76 @@
+//! Diagnostics of the API requests, enabled with `-v` flags.
+//! Lines go to stderr or to `--log-file`, so stdout stays clean for pipes
+use kaiten_cli::error::Error;
+use chrono::Local;
+use log::{Level, Log, Metadata, Record};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+struct Logger {
+    file: Option<Mutex<File>>,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        // records of reqwest and hyper are too noisy for users
+        metadata.level() <= log::max_level() && metadata.target().starts_with(env!("CARGO_CRATE_NAME"))
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format!(
+            "{} {:<5} {}",
+            Local::now().format("%Y-%m-%dT%H:%M:%S%.3f%:z"),
+            record.level(),
+            record.args()
+        );
+        match &self.file {
+            Some(file) => {
+                let _ = writeln!(file.lock().unwrap(), "{}", line);
+            }
+            None => eprintln!("{}", line),
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(file) = &self.file {
+            let _ = file.lock().unwrap().flush();
+        }
+    }
+}
+
+/// `0` - warnings only, `-v` - requests with status and timing, `-vv` - headers, `-vvv` - bodies.
+/// A log file without `-v` gets the requests as well
+pub fn init(verbose: u8, log_file: Option<&Path>) -> Result<(), Error> {
+    let level = match verbose {
+        0 if log_file.is_some() => Level::Info,
+        0 => Level::Warn,
+        1 => Level::Info,
+        2 => Level::Debug,
+        _ => Level::Trace,
+    };
+    let file = match log_file {
+        Some(path) => {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|err| {
+                    io::Error::new(
+                        err.kind(),
+                        format!("Failed to open log file {:?}: {}", path, err),
+                    )
+                })?;
+            Some(Mutex::new(file))
+        }
+        None => None,
+    };
+    log::set_boxed_logger(Box::new(Logger { file }))
+        .map_err(|err| Error::Invalid(err.to_string()))?;
+    log::set_max_level(level.to_level_filter());
+    Ok(())
+}
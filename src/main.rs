@@ -1,6 +1,5 @@
-mod models;
 mod command;
-mod api;
+mod logger;
 
 use crate::command::Cli;
 use clap::Parser;
@@ -8,20 +7,29 @@ use termimad::{MadSkin, rgb};
 
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() {
     let cli = Cli::parse();
-    let result = match cli.execute().await {
+    let result = match cli.execute(&mut std::io::stdout()).await {
         Ok(result) => {result},
         Err(err) => {
-            eprintln!("{}", err);
-            std::process::exit(1)
+            eprintln!("Error: {}", err);
+            if let Some(hint) = err.hint() {
+                eprintln!("Hint: {}", hint);
+            }
+            std::process::exit(err.exit_code())
         }
     };
+    if !cli.output.is_table() {
+        // streamed rows are already printed
+        if !result.is_empty() {
+            println!("{}", result);
+        }
+        return;
+    }
     let mut skin = MadSkin::default();
     println!("{}", skin.inline(&result));
     skin.set_headers_fg(rgb(255, 187, 0));
     // println!("{}", result);
     // skin.print_text(&result);
     // println!("{}", skin.inline(&table));
-    Ok(())
 }
@@ -1,12 +1,15 @@
-use super::{RelatedCard, User};
+use super::{Card, RelatedCard, User};
 use serde_derive::{Deserialize, Serialize};
 use tabled::Tabled;
 
 #[derive(Serialize, Deserialize, Debug, Tabled, Clone)]
 pub struct Blocker {
     id: u32,
-    #[tabled(skip)]
+    #[tabled(display_with = "Self::display_option_string")]
     reason: Option<String>,
+    #[tabled(rename = "blocking card", display_with = "Self::display_option_id")]
+    #[serde(default)]
+    blocker_card_id: Option<u32>,
     #[tabled(display_with = "Self::display_user")]
     blocker: Option<User>,
     released: bool,
@@ -17,7 +20,56 @@ pub struct Blocker {
     card: Option<RelatedCard>,
 }
 
+/// Payload for `POST cards/<id>/blockers`
+#[derive(Serialize, Debug)]
+pub struct NewBlocker {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    blocker_card_id: Option<u32>,
+}
+
+/// Row of the board-wide blockers view
+#[derive(Debug, Tabled)]
+pub struct BlockedCard {
+    #[tabled(rename = "card")]
+    card_id: u32,
+    title: String,
+    reason: String,
+    #[tabled(rename = "blocking card")]
+    blocking_card: String,
+    #[tabled(rename = "raised by")]
+    raised_by: String,
+    #[tabled(rename = "for")]
+    duration: String,
+}
+
+impl NewBlocker {
+    pub fn from(reason: Option<String>, blocker_card_id: Option<u32>) -> Self {
+        NewBlocker {
+            reason,
+            blocker_card_id,
+        }
+    }
+}
+
+impl BlockedCard {
+    pub fn from(card: &Card, blocker: &Blocker) -> Self {
+        BlockedCard {
+            card_id: card.get_id(),
+            title: card.get_title().to_string(),
+            reason: blocker.get_reason().to_string(),
+            blocking_card: Blocker::display_option_id(&blocker.get_blocker_card_id()),
+            raised_by: Blocker::display_user(&blocker.blocker),
+            duration: Card::display_move_diff(&blocker.created),
+        }
+    }
+}
+
 impl Blocker {
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
     pub fn get_reason(&self) -> &str {
         if let Some(reason) = &self.reason {
             reason.as_str()
@@ -28,11 +80,23 @@ impl Blocker {
     pub fn get_card(&self) -> Option<RelatedCard> {
         self.card.clone()
     }
+    pub fn get_blocker_card_id(&self) -> Option<u32> {
+        self.blocker_card_id.or(self.card.as_ref().map(|c| c.get_id()))
+    }
+    pub fn is_released(&self) -> bool {
+        self.released
+    }
 
     fn display_user(u: &Option<User>) -> String {
         match u {
-            Some(user) => format!("{}", user.get_username()),
-            None => format!(""),
+            Some(user) => user.get_username().to_string(),
+            None => String::new(),
         }
     }
+    fn display_option_string(s: &Option<String>) -> String {
+        s.clone().unwrap_or_default()
+    }
+    fn display_option_id(id: &Option<u32>) -> String {
+        id.map(|id| id.to_string()).unwrap_or_default()
+    }
 }
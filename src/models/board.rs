@@ -1,6 +1,7 @@
 
 use serde_derive::{Deserialize, Serialize};
 use tabled::Tabled;
+use super::common::Candidate;
 use super::{Column, Lane};
 #[derive(Serialize, Deserialize, Debug, Tabled, Clone)]
 pub struct Board{
@@ -27,4 +28,58 @@ impl Board {
     pub fn get_space_id(&self) -> u32 {
         self.space_id
     }
+
+    pub fn get_title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn first_column(&self) -> Option<Column> {
+        self.get_columns()
+            .into_iter()
+            .min_by(|a, b| a.sort_order.partial_cmp(&b.sort_order).unwrap())
+    }
+
+    pub fn first_lane(&self) -> Option<Lane> {
+        self.get_lanes()
+            .into_iter()
+            .min_by(|a, b| a.sort_order.partial_cmp(&b.sort_order).unwrap())
+    }
+
+    /// Columns and subcolumns with `Parent/Child` labels for name matching
+    pub fn column_candidates(&self) -> Vec<Candidate<Column>> {
+        let mut columns = self.get_columns();
+        columns.sort_by(|a, b| a.sort_order.partial_cmp(&b.sort_order).unwrap());
+        let mut candidates = Vec::new();
+        for col in columns {
+            let mut subcolumns = col.subcolumns.clone().unwrap_or_default();
+            subcolumns.sort_by(|a, b| a.sort_order.partial_cmp(&b.sort_order).unwrap());
+            let parent_title = col.get_title().to_string();
+            candidates.push(Candidate::new(parent_title.clone(), parent_title.clone(), col.get_id(), col));
+            for sub in subcolumns {
+                let label = format!("{}/{}", parent_title, sub.get_title());
+                candidates.push(Candidate::new(label, sub.get_title().to_string(), sub.get_id(), sub));
+            }
+        }
+        candidates
+    }
+
+    pub fn lane_candidates(&self) -> Vec<Candidate<Lane>> {
+        let mut lanes = self.get_lanes();
+        lanes.sort_by(|a, b| a.sort_order.partial_cmp(&b.sort_order).unwrap());
+        lanes
+            .into_iter()
+            .map(|lane| {
+                let title = lane.get_title().to_string();
+                Candidate::new(title.clone(), title, lane.get_id(), lane)
+            })
+            .collect()
+    }
+
+    /// Column title, `Parent/Child` for subcolumns
+    pub fn column_path(&self, column_id: u32) -> Option<String> {
+        self.column_candidates()
+            .into_iter()
+            .find(|c| c.id == column_id)
+            .map(|c| c.label)
+    }
 }
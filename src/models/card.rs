@@ -1,16 +1,19 @@
 use chrono::prelude::*;
 use colored::Colorize;
 use serde_derive::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::io::{Read, Write};
-use std::{fs::File, process::Command};
 use tabled::{
-    settings::{object::Rows, Alignment, Disable, Panel, Span, Style, Width},
+    settings::{
+        location::ByColumnName,
+        object::{Columns, Rows},
+        Alignment, Disable, Panel, Span, Style, Width,
+    },
     Table, Tabled,
 };
 
+use crate::models::due_date::display_due_date;
 use crate::models::*;
-use tempfile::Builder;
 
 #[derive(Serialize, Deserialize, Debug, Tabled)]
 pub struct Card {
@@ -47,10 +50,19 @@ pub struct Card {
     tags: Option<Vec<Tag>>,
     #[tabled(skip)]
     description: Option<String>,
+    #[tabled(display_with("Self::display_due_date", self), rename = "due")]
+    #[serde(default, skip_serializing)]
+    due_date: Option<String>,
     #[tabled(skip)]
     pub archived: bool,
     #[tabled(skip)]
+    #[serde(default, skip_serializing)]
+    state: u8,
+    #[tabled(skip)]
     created: String,
+    #[tabled(skip)]
+    #[serde(default, skip_serializing)]
+    updated: String,
     // #[tabled(skip)]
     #[tabled(display_with = "Self::display_move_diff", rename = "moved")]
     last_moved_at: String,
@@ -101,36 +113,6 @@ impl RelatedCard {
 }
 
 impl Card {
-    #[allow(dead_code)]
-    fn new() -> Card {
-        Card {
-            id: 1,
-            title: "Title".to_string(),
-            column: Column::new(),
-            column_id: 0,
-            board_id: 0,
-            lane_id: 0,
-            lane: Lane::new(),
-            blocked: false,
-            blocking_card: None,
-            block_reason: String::new(),
-            properties: None,
-            r#type: CardType::new(),
-            tags: None,
-            sort_order: 0.0,
-            members: None,
-            description: Some("".to_string()),
-            archived: false,
-            created: String::new(),
-            last_moved_at: String::new(),
-            checklists: None,
-            parents: None,
-            children: None,
-            blockers: None,
-            blocking_blockers: None,
-        }
-    }
-
     fn process_blocker(
         proccessed: &mut Vec<[String; 3]>,
         blockers: Option<Vec<Blocker>>,
@@ -162,8 +144,32 @@ impl Card {
         }
     }
 
+    /// Table of `cards ls`, the due column is only shown with `show_due_date`
+    pub fn list_to_table_string(
+        cards: &[Card],
+        show_due_date: bool,
+        now: DateTime<FixedOffset>,
+    ) -> String {
+        let mut table = Table::new(cards.iter().map(|card| CardRow { card, now }));
+        if !show_due_date {
+            table.with(Disable::column(ByColumnName::new("due")));
+        }
+        table
+            .modify(Columns::first(), Width::increase(10))
+            .modify(Columns::single(1), Width::wrap(70).keep_words())
+            .modify(Columns::single(2), Width::increase(10))
+            .with(Style::modern())
+            .with(Width::wrap(135).keep_words())
+            .to_string()
+    }
+
     pub fn to_table_string(&self) -> String {
-        let desc = self.description.clone().unwrap_or(String::new());
+        self.to_table_string_at(Local::now().fixed_offset())
+    }
+
+    /// Card details with the move time and the due date relative to `now`
+    pub fn to_table_string_at(&self, now: DateTime<FixedOffset>) -> String {
+        let desc = self.description.clone().unwrap_or_default();
         let mut proccessed: Vec<[String; 3]> = Vec::new();
         Self::process_blocker(&mut proccessed, self.blocking_blockers.clone(), false);
         Self::process_blocker(&mut proccessed, self.blockers.clone(), true);
@@ -193,10 +199,18 @@ impl Card {
             [
                 self.get_string_tags(),
                 self.get_string_members(),
-                Self::display_move_diff(&self.last_moved_at),
+                Self::display_move_diff_at(&self.last_moved_at, now.to_utc()),
             ],
         ]);
 
+        if self.due_date.is_some() {
+            table_data.push([
+                format!("due {}", self.display_due_date_at(now.date_naive())),
+                String::new(),
+                String::new(),
+            ]);
+        }
+        let extra_rows = table_data.len() - 4 + blockers_len;
         table_data.extend(proccessed);
         table_data.extend([
             [desc, String::new(), String::new()],
@@ -215,30 +229,45 @@ impl Card {
             .with(Style::modern())
             .with(Disable::row(Rows::first()))
             .with(Panel::header(title))
-            .modify((5 + blockers_len, 0), Span::column(3))
-            .modify((6 + blockers_len, 0), Span::column(3))
+            .modify((5 + extra_rows, 0), Span::column(3))
+            .modify((6 + extra_rows, 0), Span::column(3))
             .with(Alignment::center())
             .with(Width::wrap(130).keep_words())
             .to_string()
     }
 
-    fn calculate_hour_diff(lst: &String) -> i64 {
-        let parse_date = lst.parse::<DateTime<Utc>>();
-        if parse_date.is_err() {
-            return -999;
-        } else {
-            let target_date = parse_date.unwrap().with_timezone(&Utc);
-            let now = Utc::now();
-            let duration = now.signed_duration_since(target_date);
-            duration.num_hours()
+    fn calculate_hour_diff(lst: &str, now: DateTime<Utc>) -> i64 {
+        match lst.parse::<DateTime<Utc>>() {
+            Ok(target_date) => {
+                let duration = now.signed_duration_since(target_date);
+                duration.num_hours()
+            }
+            Err(_) => -999,
         }
     }
 
-    fn display_move_diff(lst: &String) -> String {
-        let mut hour_diff = Self::calculate_hour_diff(lst);
+    /// Due date for tables, red when overdue
+    pub fn display_due_date(&self) -> String {
+        self.display_due_date_at(Local::now().date_naive())
+    }
+
+    pub fn display_due_date_at(&self, today: NaiveDate) -> String {
+        match &self.due_date {
+            Some(due_date) => display_due_date(due_date, today, self.is_done()),
+            None => String::new(),
+        }
+    }
+
+    pub fn display_move_diff(lst: &str) -> String {
+        Self::display_move_diff_at(lst, Utc::now())
+    }
+
+    /// Time since `lst` like `5h` or `3d2h`, red after a day
+    pub fn display_move_diff_at(lst: &str, now: DateTime<Utc>) -> String {
+        let mut hour_diff = Self::calculate_hour_diff(lst, now);
         let diff_string = if hour_diff > 24 {
             let day_diff = hour_diff / 24;
-            hour_diff = hour_diff % 24;
+            hour_diff %= 24;
             let s = format!("{}d{}h", day_diff, hour_diff);
             s.red().to_string()
         } else {
@@ -247,28 +276,59 @@ impl Card {
         diff_string
     }
 
-    pub fn from_string() -> String {
-        let text = String::new();
-        let editor = env!("EDITOR");
-        let mut tmpfile = Builder::new()
-            .suffix(".md")
-            .rand_bytes(5)
-            .tempfile()
-            .unwrap();
-        tmpfile.write(text.as_bytes()).expect("Can't write");
-        let path = tmpfile.into_temp_path();
-
-        Command::new(editor)
-            .arg(&path)
-            .status()
-            .expect("Something went wrong");
-
-        let mut card_text = String::new();
-        File::open(&path)
-            .expect("Could not open file")
-            .read_to_string(&mut card_text)
-            .expect("Could not read");
-        card_text
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+    pub fn get_title(&self) -> &str {
+        &self.title
+    }
+    pub fn get_board_id(&self) -> u32 {
+        self.board_id
+    }
+    pub fn get_column_id(&self) -> u32 {
+        self.column_id
+    }
+    pub fn get_lane_id(&self) -> u32 {
+        self.lane_id
+    }
+    pub fn get_state(&self) -> u8 {
+        self.state
+    }
+    pub fn is_done(&self) -> bool {
+        self.state == 3
+    }
+    pub fn get_responsible(&self) -> Option<User> {
+        self.get_members().into_iter().find(|m| m.is_responsible())
+    }
+    pub fn is_blocked(&self) -> bool {
+        self.blocked
+    }
+    pub fn get_column(&self) -> &Column {
+        &self.column
+    }
+    pub fn get_created(&self) -> &str {
+        &self.created
+    }
+    pub fn get_last_moved_at(&self) -> &str {
+        &self.last_moved_at
+    }
+    pub fn get_lane(&self) -> &Lane {
+        &self.lane
+    }
+    pub fn get_type(&self) -> &CardType {
+        &self.r#type
+    }
+    pub fn get_description(&self) -> &str {
+        self.description.as_deref().unwrap_or_default()
+    }
+    pub fn get_updated(&self) -> &str {
+        &self.updated
+    }
+    pub fn get_due_date(&self) -> Option<&str> {
+        self.due_date.as_deref()
+    }
+    pub fn get_checklists(&self) -> Vec<Checklist> {
+        self.checklists.clone().unwrap_or_default()
     }
 
     pub fn get_tags(&self) -> Vec<Tag> {
@@ -329,35 +389,7 @@ impl Card {
         }
     }
 
-    pub fn set_column_id(&mut self, column_id: u32) {
-        self.column_id = column_id
-    }
-    pub fn set_board_id(&mut self, board_id: u32) {
-        self.board_id = board_id
-    }
-    pub fn set_lane_id(&mut self, lane_id: u32) {
-        self.lane_id = lane_id
-    }
-
-    pub fn set_description(&mut self, description: String, append: bool) {
-        let new_desc = match &self.description {
-            Some(desc) => {
-                if append {
-                    format!("{}\n{}", desc, description)
-                } else {
-                    description
-                }
-            }
-            None => description,
-        };
-        self.description = Some(new_desc);
-    }
-
-    pub fn set_title(&mut self, title: String) {
-        self.title = title;
-    }
-
-    fn is_member(&self, username: &str) -> bool {
+    pub fn is_member(&self, username: &str) -> bool {
         if let Some(members) = &self.members {
             members.iter().any(|m| m.is_username(username))
         } else {
@@ -373,26 +405,55 @@ impl Card {
     }
 }
 
+/// Row of the cards table with `moved` and `due` relative to `now` instead of the clock
+struct CardRow<'a> {
+    card: &'a Card,
+    now: DateTime<FixedOffset>,
+}
+
+impl Tabled for CardRow<'_> {
+    const LENGTH: usize = Card::LENGTH;
+
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        Card::headers()
+            .iter()
+            .zip(self.card.fields())
+            .map(|(header, field)| match header.as_ref() {
+                "moved" => Cow::Owned(Card::display_move_diff_at(
+                    &self.card.last_moved_at,
+                    self.now.to_utc(),
+                )),
+                "due" => Cow::Owned(self.card.display_due_date_at(self.now.date_naive())),
+                _ => field,
+            })
+            .collect()
+    }
+
+    fn headers() -> Vec<Cow<'static, str>> {
+        Card::headers()
+    }
+}
+
 fn display_members(o: &Option<Vec<User>>) -> String {
     match o {
         Some(members) => {
             let mems: Vec<&str> = members
-                .into_iter()
+                .iter()
                 .filter(|m| m.is_responsible())
                 .map(|m| m.get_username())
                 .collect();
-            format!("{}", mems.join(",\n"))
+            mems.join(",\n")
         }
-        None => format!(""),
+        None => String::new(),
     }
 }
 
 fn display_tags(tags: &Option<Vec<Tag>>) -> String {
     match tags {
         Some(tags) => {
-            let str_tags: Vec<&str> = tags.into_iter().map(|tag| tag.get_name()).collect();
-            format!("{}", str_tags.join("\n"))
+            let str_tags: Vec<&str> = tags.iter().map(|tag| tag.get_name()).collect();
+            str_tags.join("\n")
         }
-        None => format!(""),
+        None => String::new(),
     }
 }
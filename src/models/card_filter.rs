@@ -0,0 +1,258 @@
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_derive::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Comma separated ids like `1,2,3`, validated when parsed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IdList(pub Vec<u32>);
+
+impl FromStr for IdList {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let ids = s
+            .split(',')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(|id| {
+                id.parse::<u32>().map_err(|_| {
+                    format!("`{}` in `{}` is not an id, use comma separated numbers like 1,2,3", id, s)
+                })
+            })
+            .collect::<Result<Vec<u32>, String>>()?;
+        if ids.is_empty() {
+            return Err(format!("`{}` has no ids, use comma separated numbers like 1,2,3", s));
+        }
+        Ok(IdList(ids))
+    }
+}
+
+impl IdList {
+    /// Ids of both lists without repeats, in the order they come
+    pub fn union(list: Option<IdList>, other: &Option<IdList>) -> Option<IdList> {
+        match (list, other) {
+            (Some(IdList(mut ids)), Some(IdList(other))) => {
+                for id in other {
+                    if !ids.contains(id) {
+                        ids.push(*id);
+                    }
+                }
+                Some(IdList(ids))
+            }
+            (list, other) => list.or_else(|| other.clone()),
+        }
+    }
+}
+
+impl fmt::Display for IdList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ids: Vec<String> = self.0.iter().map(|id| id.to_string()).collect();
+        write!(f, "{}", ids.join(","))
+    }
+}
+
+/// Sent and saved in yaml as `1,2,3`, the API format
+impl Serialize for IdList {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+/// Also accepts a single number and a list, yaml parses `5` as a number.
+/// A visitor instead of an untagged enum keeps the position of the errors in the file
+impl<'de> Deserialize<'de> for IdList {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct IdListVisitor;
+
+        impl<'de> Visitor<'de> for IdListVisitor {
+            type Value = IdList;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "comma separated ids, a number or a list of numbers")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<IdList, E> {
+                value.parse().map_err(E::custom)
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<IdList, E> {
+                let id = u32::try_from(value)
+                    .map_err(|_| E::custom(format!("`{}` is not an id", value)))?;
+                Ok(IdList(vec![id]))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<IdList, E> {
+                let id = u32::try_from(value)
+                    .map_err(|_| E::custom(format!("`{}` is not an id", value)))?;
+                Ok(IdList(vec![id]))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<IdList, A::Error> {
+                let mut ids = Vec::new();
+                while let Some(id) = seq.next_element::<u32>()? {
+                    ids.push(id);
+                }
+                if ids.is_empty() {
+                    return Err(de::Error::custom("the list has no ids"));
+                }
+                Ok(IdList(ids))
+            }
+        }
+
+        deserializer.deserialize_any(IdListVisitor)
+    }
+}
+
+/// Query of `GET /cards`. Unset fields are not sent.
+/// Filters are built in layers with [`CardFilter::merge`]: defaults, config, a saved query, CLI args
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct CardFilter {
+    /// 1 - on board, 2 - archived
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<u8>,
+    /// 1 - queued, 2 - in progress, 3 - done
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub states: Option<IdList>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub space_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub board_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lane_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub responsible_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column_ids: Option<IdList>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag_ids: Option<IdList>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_ids: Option<IdList>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub member_ids: Option<IdList>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner_ids: Option<IdList>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub responsible_ids: Option<IdList>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclude_board_ids: Option<IdList>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclude_lane_ids: Option<IdList>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclude_column_ids: Option<IdList>,
+    /// Text search
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_before: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_after: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_before: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_after: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_moved_in_progress_after: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_moved_in_progress_before: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_moved_to_done_at_after: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_moved_to_done_at_before: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_date_after: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_date_before: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archived: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asap: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overdue: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub done_on_time: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub with_due_date: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_space_id: Option<u32>,
+    /// Page size, Kaiten returns at most 100 cards
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
+}
+
+impl CardFilter {
+    /// Cards on the board which are not done yet, the defaults of `cards ls`
+    pub fn not_done() -> CardFilter {
+        CardFilter {
+            condition: Some(1),
+            states: Some(IdList(vec![1, 2])),
+            ..CardFilter::default()
+        }
+    }
+
+    /// Fields which are set in `other` replace the fields of `self`,
+    /// the exclude lists are joined so a flag does not bring back what the config hides.
+    /// No `..` below, so a new field can't be forgotten here
+    pub fn merge(self, other: &CardFilter) -> CardFilter {
+        CardFilter {
+            condition: other.condition.or(self.condition),
+            states: other.states.clone().or(self.states),
+            space_id: other.space_id.or(self.space_id),
+            board_id: other.board_id.or(self.board_id),
+            column_id: other.column_id.or(self.column_id),
+            lane_id: other.lane_id.or(self.lane_id),
+            type_id: other.type_id.or(self.type_id),
+            responsible_id: other.responsible_id.or(self.responsible_id),
+            owner_id: other.owner_id.or(self.owner_id),
+            column_ids: other.column_ids.clone().or(self.column_ids),
+            tag_ids: other.tag_ids.clone().or(self.tag_ids),
+            type_ids: other.type_ids.clone().or(self.type_ids),
+            member_ids: other.member_ids.clone().or(self.member_ids),
+            owner_ids: other.owner_ids.clone().or(self.owner_ids),
+            responsible_ids: other.responsible_ids.clone().or(self.responsible_ids),
+            exclude_board_ids: IdList::union(self.exclude_board_ids, &other.exclude_board_ids),
+            exclude_lane_ids: IdList::union(self.exclude_lane_ids, &other.exclude_lane_ids),
+            exclude_column_ids: IdList::union(self.exclude_column_ids, &other.exclude_column_ids),
+            query: other.query.clone().or(self.query),
+            tag: other.tag.clone().or(self.tag),
+            created_before: other.created_before.clone().or(self.created_before),
+            created_after: other.created_after.clone().or(self.created_after),
+            updated_before: other.updated_before.clone().or(self.updated_before),
+            updated_after: other.updated_after.clone().or(self.updated_after),
+            first_moved_in_progress_after: other.first_moved_in_progress_after.clone().or(self.first_moved_in_progress_after),
+            first_moved_in_progress_before: other.first_moved_in_progress_before.clone().or(self.first_moved_in_progress_before),
+            last_moved_to_done_at_after: other.last_moved_to_done_at_after.clone().or(self.last_moved_to_done_at_after),
+            last_moved_to_done_at_before: other.last_moved_to_done_at_before.clone().or(self.last_moved_to_done_at_before),
+            due_date_after: other.due_date_after.clone().or(self.due_date_after),
+            due_date_before: other.due_date_before.clone().or(self.due_date_before),
+            archived: other.archived.or(self.archived),
+            asap: other.asap.or(self.asap),
+            overdue: other.overdue.or(self.overdue),
+            done_on_time: other.done_on_time.or(self.done_on_time),
+            with_due_date: other.with_due_date.or(self.with_due_date),
+            order_space_id: other.order_space_id.or(self.order_space_id),
+            limit: other.limit.or(self.limit),
+            offset: other.offset.or(self.offset),
+        }
+    }
+
+    /// Same filter for another page
+    pub fn page(&self, limit: u32, offset: u32) -> CardFilter {
+        CardFilter {
+            limit: Some(limit),
+            offset: Some(offset),
+            ..self.clone()
+        }
+    }
+}
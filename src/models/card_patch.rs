@@ -0,0 +1,39 @@
+use serde_derive::Serialize;
+
+/// Payload for `PATCH cards/<id>` with only the fields which were changed
+#[derive(Serialize, Debug, Default)]
+pub struct CardPatch {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub board_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lane_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asap: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_text: Option<String>,
+    /// `Some(None)` clears the due date
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<Option<String>>,
+}
+
+impl CardPatch {
+    /// Names of the fields which will be sent
+    pub fn changed_fields(&self) -> Vec<String> {
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::Object(fields)) => fields.keys().cloned().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changed_fields().is_empty()
+    }
+}
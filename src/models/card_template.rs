@@ -0,0 +1,313 @@
+use super::common::eq_ignore_case;
+use super::due_date::parse_due_date;
+use super::{Board, Checklist, Column, Info, Lane, User};
+use chrono::Local;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_derive::{Deserialize, Serialize};
+
+const FRONT_MATTER_DELIMITER: &str = "---";
+const CHECKLISTS_MARKER: &str = "<!-- checklists: `### name` followed by `[ ] item` / `[x] item` lines -->";
+
+/// Card fields which can be changed on an existing card with `PATCH cards/<id>`
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct CardFields {
+    pub title: Option<String>,
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub board: Option<String>,
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub column: Option<String>,
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub lane: Option<String>,
+    #[serde(rename = "type")]
+    pub r#type: Option<String>,
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub due_date: Option<String>,
+}
+
+/// Editable card fields as a YAML front-matter block followed by a Markdown description
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct CardTemplate {
+    #[serde(flatten)]
+    pub fields: CardFields,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub responsible: Option<String>,
+    #[serde(skip)]
+    pub description: String,
+}
+
+/// Existing card rendered for `cards edit`: fields, description and checklists
+#[derive(Debug, Default)]
+pub struct CardEditTemplate {
+    pub fields: CardFields,
+    pub description: String,
+    /// `None` when the checklists section was removed from the text
+    pub checklists: Option<Vec<Checklist>>,
+}
+
+/// Payload for `POST cards`
+#[derive(Serialize, Debug)]
+pub struct NewCard {
+    title: String,
+    board_id: u32,
+    column_id: u32,
+    lane_id: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    type_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    due_date: Option<String>,
+}
+
+/// Card fields with names resolved into entities from the cache
+#[derive(Debug)]
+pub struct ResolvedFields {
+    pub title: String,
+    pub board: Board,
+    pub column: Column,
+    pub lane: Lane,
+    pub type_id: Option<u32>,
+    pub due_date: Option<String>,
+}
+
+/// Card ready to be created: the payload plus the fields set via separate endpoints
+#[derive(Debug)]
+pub struct ResolvedCard {
+    pub card: NewCard,
+    pub tags: Vec<String>,
+    pub responsible: Option<User>,
+}
+
+fn string_or_number<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value: Option<serde_yaml::Value> = Deserialize::deserialize(deserializer)?;
+    match value {
+        None | Some(serde_yaml::Value::Null) => Ok(None),
+        Some(serde_yaml::Value::String(s)) if s.trim().is_empty() => Ok(None),
+        Some(serde_yaml::Value::String(s)) => Ok(Some(s.trim().to_string())),
+        Some(serde_yaml::Value::Number(n)) => Ok(Some(n.to_string())),
+        Some(_) => Err(serde::de::Error::custom("expected a string or a number")),
+    }
+}
+
+impl CardFields {
+    /// Resolve board, column, lane and type names through the cached `Info`
+    pub fn resolve(&self, info: &Info, default_board_id: Option<u32>) -> Result<ResolvedFields, String> {
+        let title = match &self.title {
+            Some(title) if !title.trim().is_empty() => title.trim().to_string(),
+            _ => return Err("Card title is required".to_string()),
+        };
+        // an unchanged title keeps the current board even if another one has the same title
+        let current = default_board_id.and_then(|board_id| info.get_board(board_id));
+        let board = match (&self.board, default_board_id) {
+            (Some(board), _) => match current.filter(|b| eq_ignore_case(b.get_title(), board.trim())) {
+                Some(current) => current,
+                None => info.find_board(board)?,
+            },
+            (None, Some(board_id)) => info
+                .get_board(board_id)
+                .ok_or(format!("Board with id {} not found", board_id))?,
+            (None, None) => return Err("Board is required".to_string()),
+        };
+        let column = match &self.column {
+            Some(column) => {
+                let column = info.resolve_column(column, Some(board.get_id()))?;
+                if column.get_board_id() != board.get_id() {
+                    return Err(format!(
+                        "Column {} is not on board `{}`",
+                        column.get_id(),
+                        board.get_title()
+                    ));
+                }
+                column
+            }
+            None => board
+                .first_column()
+                .ok_or(format!("Board `{}` has no columns", board.get_title()))?,
+        };
+        let lane = match &self.lane {
+            Some(lane) => {
+                let lane = info.resolve_lane(lane, Some(board.get_id()))?;
+                if lane.get_board_id() != board.get_id() {
+                    return Err(format!(
+                        "Lane {} is not on board `{}`",
+                        lane.get_id(),
+                        board.get_title()
+                    ));
+                }
+                lane
+            }
+            None => board
+                .first_lane()
+                .ok_or(format!("Board `{}` has no lanes", board.get_title()))?,
+        };
+        let type_id = match &self.r#type {
+            Some(letter) => Some(
+                info.find_card_type(letter)
+                    .ok_or(format!("Card type `{}` not found", letter))?
+                    .get_id(),
+            ),
+            None => None,
+        };
+        let due_date = match &self.due_date {
+            Some(due_date) => parse_due_date(due_date, Local::now().date_naive())?,
+            None => None,
+        };
+        Ok(ResolvedFields {
+            title,
+            board,
+            column,
+            lane,
+            type_id,
+            due_date,
+        })
+    }
+}
+
+impl CardTemplate {
+    /// Empty template with the board defaults prefilled
+    pub fn new(board: Option<&Board>) -> Self {
+        let mut template = Self::default();
+        if let Some(board) = board {
+            template.fields.board = Some(board.get_title().to_string());
+            template.fields.column = board.first_column().map(|c| c.get_title().to_string());
+            template.fields.lane = board.first_lane().map(|l| l.get_title().to_string());
+        }
+        template
+    }
+
+    /// Render the template as front-matter and description ready for `$EDITOR`
+    pub fn to_text(&self) -> Result<String, serde_yaml::Error> {
+        render_front_matter(self, &self.description)
+    }
+
+    /// Parse the text saved in `$EDITOR` back into the template
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        let (mut template, description): (Self, &str) = parse_front_matter(text)?;
+        template.description = description.trim().to_string();
+        Ok(template)
+    }
+
+    /// Resolve all names through the cached `Info` into the payload for `POST cards`
+    pub fn resolve(&self, info: &Info, default_board_id: Option<u32>) -> Result<ResolvedCard, String> {
+        let fields = self.fields.resolve(info, default_board_id)?;
+        let responsible = match &self.responsible {
+            Some(username) => Some(
+                info.get_user(username, Some(fields.board.get_space_id()))
+                    .ok_or(format!("User `{}` not found", username))?,
+            ),
+            None => None,
+        };
+        let description = if self.description.is_empty() {
+            None
+        } else {
+            Some(self.description.clone())
+        };
+        Ok(ResolvedCard {
+            card: NewCard {
+                title: fields.title,
+                board_id: fields.board.get_id(),
+                column_id: fields.column.get_id(),
+                lane_id: fields.lane.get_id(),
+                type_id: fields.type_id,
+                description,
+                due_date: fields.due_date,
+            },
+            tags: self.tags.clone(),
+            responsible,
+        })
+    }
+}
+
+impl CardEditTemplate {
+    /// Render fields, description and checklists ready for `$EDITOR`
+    pub fn to_text(&self) -> Result<String, serde_yaml::Error> {
+        let checklists = self
+            .checklists
+            .as_ref()
+            .map(|checklists| {
+                checklists
+                    .iter()
+                    .map(|c| c.to_string())
+                    .collect::<Vec<String>>()
+                    .join("\n\n")
+            })
+            .unwrap_or_default();
+        let body = format!("{}\n\n{}\n{}\n", self.description, CHECKLISTS_MARKER, checklists);
+        render_front_matter(&self.fields, &body)
+    }
+
+    /// Parse the text saved in `$EDITOR` back into fields, description and checklists
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        let (fields, body): (CardFields, &str) = parse_front_matter(text)?;
+        let (description, checklists) = match body.split_once(CHECKLISTS_MARKER) {
+            Some((description, checklists)) => {
+                // `checklists` is the end of `text`, its first line is the one of the marker
+                let first_line = text[..text.len() - checklists.len()].matches('\n').count() + 1;
+                (description, Some(parse_checklists(checklists, first_line)?))
+            }
+            None => (body, None),
+        };
+        Ok(Self {
+            fields,
+            description: description.trim().to_string(),
+            checklists,
+        })
+    }
+}
+
+/// Split checklists section into `### name` blocks and parse each of them,
+/// `first_line` is the line number of `text` in the edited file
+fn parse_checklists(text: &str, first_line: usize) -> Result<Vec<Checklist>, String> {
+    // blocks with the line number of their `### name`
+    let mut blocks: Vec<(usize, String)> = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        let number = first_line + idx;
+        if line.trim_start().starts_with("### ") {
+            blocks.push((number, format!("{}\n", line.trim())));
+        } else if let Some((_, block)) = blocks.last_mut() {
+            block.push_str(line);
+            block.push('\n');
+        } else if !line.trim().is_empty() {
+            return Err(format!(
+                "Line {}: `{}` is not in a checklist, add a `### name` line before it",
+                number,
+                line.trim()
+            ));
+        }
+    }
+    blocks
+        .into_iter()
+        .map(|(number, block)| Checklist::from_text(&block, number))
+        .collect()
+}
+
+fn render_front_matter<T: Serialize>(front_matter: &T, body: &str) -> Result<String, serde_yaml::Error> {
+    let yaml = serde_yaml::to_string(front_matter)?;
+    let yaml = yaml
+        .trim_start_matches(FRONT_MATTER_DELIMITER)
+        .trim_start()
+        .replace(": ~\n", ":\n");
+    Ok(format!(
+        "{}\n{}{}\n\n{}",
+        FRONT_MATTER_DELIMITER, yaml, FRONT_MATTER_DELIMITER, body
+    ))
+}
+
+/// Split `---\n<yaml>\n---\n<body>` and deserialize the yaml
+fn parse_front_matter<T: DeserializeOwned>(text: &str) -> Result<(T, &str), String> {
+    let err = || format!("Card text must start with a `{}` front-matter block", FRONT_MATTER_DELIMITER);
+    let text = text.trim_start();
+    let rest = text.strip_prefix(FRONT_MATTER_DELIMITER).ok_or_else(err)?;
+    let end = rest
+        .find(&format!("\n{}", FRONT_MATTER_DELIMITER))
+        .ok_or_else(err)?;
+    let front_matter: T = serde_yaml::from_str(&rest[..end])
+        .map_err(|err| format!("Failed to parse card front-matter: {}", err))?;
+    let body = &rest[end + 1 + FRONT_MATTER_DELIMITER.len()..];
+    Ok((front_matter, body))
+}
@@ -0,0 +1,90 @@
+use super::{Card, Info};
+use colored::Colorize;
+use std::collections::{HashMap, HashSet};
+
+/// Card with its descendants fetched up to a depth limit
+pub struct CardTree {
+    card: Card,
+    children: Vec<CardTree>,
+    /// Amount of children which were not fetched because of the depth limit
+    truncated: usize,
+}
+
+impl CardTree {
+    /// Build tree from `root_id` using already fetched cards, each card is visited once
+    pub fn build(root_id: u32, cards: &mut HashMap<u32, Card>) -> Option<Self> {
+        let mut visited = HashSet::new();
+        Self::build_node(root_id, cards, &mut visited)
+    }
+
+    fn build_node(id: u32, cards: &mut HashMap<u32, Card>, visited: &mut HashSet<u32>) -> Option<Self> {
+        if !visited.insert(id) {
+            return None;
+        }
+        let card = cards.remove(&id)?;
+        let mut children = Vec::new();
+        let mut truncated = 0;
+        for child in card.get_childrens() {
+            match Self::build_node(child.get_id(), cards, visited) {
+                Some(node) => children.push(node),
+                None => truncated += 1,
+            }
+        }
+        Some(CardTree {
+            card,
+            children,
+            truncated,
+        })
+    }
+
+    /// Amount of done and total descendants
+    pub fn rollup(&self) -> (usize, usize) {
+        self.children.iter().fold((0, 0), |(done, total), child| {
+            let (child_done, child_total) = child.rollup();
+            let own_done = usize::from(child.card.is_done());
+            (done + child_done + own_done, total + child_total + 1)
+        })
+    }
+
+    pub fn render(&self, info: Option<&Info>) -> String {
+        let mut lines = vec![self.line(info)];
+        self.render_children(info, "", &mut lines);
+        lines.join("\n")
+    }
+
+    fn render_children(&self, info: Option<&Info>, prefix: &str, lines: &mut Vec<String>) {
+        let count = self.children.len();
+        for (idx, child) in self.children.iter().enumerate() {
+            let last = idx + 1 == count && self.truncated == 0;
+            let (branch, indent) = if last { ("└── ", "    ") } else { ("├── ", "│   ") };
+            lines.push(format!("{}{}{}", prefix, branch, child.line(info)));
+            child.render_children(info, &format!("{}{}", prefix, indent), lines);
+        }
+        if self.truncated > 0 {
+            lines.push(format!("{}└── … {} more", prefix, self.truncated));
+        }
+    }
+
+    fn line(&self, info: Option<&Info>) -> String {
+        let card = &self.card;
+        let column = info
+            .and_then(|info| info.get_column(card.get_column_id()))
+            .map(|c| c.get_title().to_string())
+            .unwrap_or_else(|| card.get_column_id().to_string());
+        let state = match card.get_state() {
+            1 => "queued".normal(),
+            2 => "in progress".yellow(),
+            3 => "done".green(),
+            _ => "unknown".normal(),
+        };
+        let mut line = format!("#{} {} [{}, {}]", card.get_id(), card.get_title(), column, state);
+        if let Some(user) = card.get_responsible() {
+            line.push_str(&format!(" @{}", user.get_username()));
+        }
+        if !self.children.is_empty() || self.truncated > 0 {
+            let (done, total) = self.rollup();
+            line.push_str(&format!(" ({}/{} done)", done, total));
+        }
+        line
+    }
+}
@@ -1,7 +1,8 @@
 
 use serde_derive::{Deserialize, Serialize};
+use tabled::Tabled;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Tabled)]
 pub struct CardType {
     id: u32,
     name: String,
@@ -13,6 +14,12 @@ impl std::fmt::Display for CardType {
         write!(f, "{}", self.get_letter())
     }
 }
+impl Default for CardType {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CardType {
     pub fn new() -> CardType {
         CardType {
@@ -1,56 +1,265 @@
 use crate::models::*;
 use serde_derive::{Deserialize, Serialize};
+use tabled::Tabled;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Checklist {
     pub id: Option<u32>,
     pub name: String,
     pub items: Option<Vec<ChecklistItem>>,
 }
 
+/// Single API call needed to turn one list of checklists into another
+#[derive(Debug)]
+pub enum ChecklistChange {
+    Create { name: String, items: Vec<ChecklistItem> },
+    Rename { id: u32, name: String },
+    Delete { id: u32 },
+    CreateItem { checklist_id: u32, item: ChecklistItem },
+    UpdateItem { checklist_id: u32, item: ChecklistItem },
+    DeleteItem { checklist_id: u32, item_id: u32 },
+}
+
+/// Checklist item given on the command line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ItemRef {
+    /// `#` column of `checklists ls`, starting from 1
+    Index(usize),
+    /// Item id in Kaiten
+    Id(u32),
+}
+
+impl std::fmt::Display for ItemRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ItemRef::Index(index) => write!(f, "#{}", index),
+            ItemRef::Id(id) => write!(f, "with id {}", id),
+        }
+    }
+}
+
+/// Checklist item addressed by its position in `checklists ls`
+#[derive(Debug, Tabled)]
+pub struct ChecklistRow {
+    #[tabled(rename = "#")]
+    pub index: usize,
+    pub checklist: String,
+    pub item: String,
+    pub id: u32,
+    #[tabled(skip)]
+    pub checklist_id: u32,
+}
+
+impl ChecklistRow {
+    /// Number items of all card checklists in order, starting from 1
+    pub fn from_checklists(checklists: &[Checklist]) -> Vec<Self> {
+        checklists
+            .iter()
+            .filter_map(|c| c.id.map(|id| (id, c)))
+            .flat_map(|(checklist_id, c)| {
+                c.get_items().into_iter().map(move |item| (checklist_id, c.name.clone(), item))
+            })
+            .enumerate()
+            .map(|(idx, (checklist_id, checklist, item))| ChecklistRow {
+                index: idx + 1,
+                checklist,
+                item: item.to_string(),
+                id: item.id,
+                checklist_id,
+            })
+            .collect()
+    }
+
+    /// Find row by index from `checklists ls` or by item id
+    pub fn find(rows: Vec<Self>, item: ItemRef) -> Option<Self> {
+        match item {
+            ItemRef::Index(index) => rows.into_iter().find(|r| r.index == index),
+            ItemRef::Id(id) => rows.into_iter().find(|r| r.id == id),
+        }
+    }
+}
+
 impl std::fmt::Display for Checklist {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match &self.items {
             Some(value) => {
-                let items: Vec<String> = value.into_iter().map(|x| format!("{}", x)).collect();
+                let items: Vec<String> = value.iter().map(|x| format!("{}", x)).collect();
                 write!(f, "### {}\n{}", self.name, items.join("\n"))
             }
             None => {
-                write!(f, "")
+                write!(f, "### {}", self.name)
             }
         }
     }
 }
 impl Checklist {
-    pub fn to_string(&self) -> String {
-        let mut checklist = format!("### {}\n\n", self.name);
-        let items_string = if self.items.is_some() {
-            let items: Vec<String> = self
-                .items
-                .as_ref()
-                .unwrap()
-                .into_iter()
-                .map(|item| item.to_string())
-                .collect();
-            items.join("\n")
-        } else {
-            "".to_string()
+    /// Parse `### name` followed by `[ ] item` / `[x] item` lines, blank lines are skipped.
+    /// `first_line` is the line number of `text` in the edited file, used in the errors
+    pub fn from_text(text: &str, first_line: usize) -> Result<Self, String> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| (first_line + idx, line))
+            .filter(|(_, line)| !line.trim().is_empty());
+        let name = match lines.next() {
+            Some((number, line)) => line
+                .trim()
+                .strip_prefix("### ")
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .ok_or(format!("Line {}: checklist must start with `### name`, got `{}`", number, line.trim()))?,
+            None => return Err(format!("Line {}: checklist is empty", first_line)),
         };
-        checklist = format!("{}{}", checklist, items_string);
-        checklist
-    }
-    pub fn from_string(text: String) -> Self {
-        let lines: Vec<&str> = text.split("\n").filter(|line| line.len() > 3).collect();
-        println!("{:?}", lines);
-        let name = lines[0][4..].to_string();
-        let items = lines[1..]
-            .into_iter()
-            .map(|item| ChecklistItem::from_string(item.to_string()))
-            .collect();
-        Self {
+        let items = lines
+            .map(|(number, line)| {
+                ChecklistItem::from_string(line.to_string())
+                    .map_err(|err| format!("Line {}: {}", number, err))
+            })
+            .collect::<Result<Vec<ChecklistItem>, String>>()?;
+        Ok(Self {
             id: None,
             name,
             items: Some(items),
+        })
+    }
+
+    pub fn get_items(&self) -> Vec<ChecklistItem> {
+        self.items.clone().unwrap_or_default()
+    }
+
+    /// Calculate changes to turn `old` checklists into `new` ones.
+    /// Checklists and items are matched by name/text first, then renamed ones by similar text,
+    /// the rest by position. The order of items is not changed on the server.
+    pub fn diff(old: &[Checklist], new: &[Checklist]) -> Vec<ChecklistChange> {
+        let mut changes = Vec::new();
+        let (pairs, deleted, created) = pair_up(old, new, |c| c.name.as_str());
+        for (o, n) in pairs {
+            let (old_checklist, new_checklist) = (&old[o], &new[n]);
+            let Some(checklist_id) = old_checklist.id else {
+                continue;
+            };
+            if old_checklist.name != new_checklist.name {
+                changes.push(ChecklistChange::Rename {
+                    id: checklist_id,
+                    name: new_checklist.name.clone(),
+                });
+            }
+            let (old_items, new_items) = (old_checklist.get_items(), new_checklist.get_items());
+            let (item_pairs, deleted_items, created_items) =
+                pair_up(&old_items, &new_items, |i| i.text.as_str());
+            for (o, n) in item_pairs {
+                let (old_item, new_item) = (&old_items[o], &new_items[n]);
+                if old_item.text != new_item.text || old_item.checked != new_item.checked {
+                    changes.push(ChecklistChange::UpdateItem {
+                        checklist_id,
+                        item: ChecklistItem {
+                            id: old_item.id,
+                            ..new_item.clone()
+                        },
+                    });
+                }
+            }
+            for n in created_items {
+                changes.push(ChecklistChange::CreateItem {
+                    checklist_id,
+                    item: new_items[n].clone(),
+                });
+            }
+            for o in deleted_items {
+                changes.push(ChecklistChange::DeleteItem {
+                    checklist_id,
+                    item_id: old_items[o].id,
+                });
+            }
+        }
+        for n in created {
+            changes.push(ChecklistChange::Create {
+                name: new[n].name.clone(),
+                items: new[n].get_items(),
+            });
+        }
+        for o in deleted {
+            if let Some(id) = old[o].id {
+                changes.push(ChecklistChange::Delete { id });
+            }
+        }
+        changes
+    }
+}
+
+/// Pair `old` and `new` entries with equal keys, then renamed ones with similar keys,
+/// then the remaining ones in order.
+/// Returns pairs of indexes, unpaired `old` indexes and unpaired `new` indexes.
+fn pair_up<T>(
+    old: &[T],
+    new: &[T],
+    key: impl Fn(&T) -> &str,
+) -> (Vec<(usize, usize)>, Vec<usize>, Vec<usize>) {
+    let mut old_paired = vec![false; old.len()];
+    let mut pairs = Vec::new();
+    let mut new_unpaired = Vec::new();
+    for (n, new_entry) in new.iter().enumerate() {
+        let found = (0..old.len()).find(|&o| !old_paired[o] && key(&old[o]) == key(new_entry));
+        match found {
+            Some(o) => {
+                old_paired[o] = true;
+                pairs.push((o, n));
+            }
+            None => new_unpaired.push(n),
+        }
+    }
+    // an edited entry is closer to its old text than to the others,
+    // so a rename next to a removal does not take the id of the removed entry
+    let mut candidates: Vec<(f64, usize, usize)> = new_unpaired
+        .iter()
+        .flat_map(|&n| {
+            (0..old.len())
+                .filter(|&o| !old_paired[o])
+                .map(move |o| (n, o))
+        })
+        .map(|(n, o)| (similarity(key(&old[o]), key(&new[n])), o, n))
+        .filter(|(score, _, _)| *score >= SIMILAR)
+        .collect();
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+    let mut new_paired = vec![false; new.len()];
+    for (_, o, n) in candidates {
+        if !old_paired[o] && !new_paired[n] {
+            old_paired[o] = true;
+            new_paired[n] = true;
+            pairs.push((o, n));
+        }
+    }
+    let mut old_unpaired = (0..old.len()).filter(|&o| !old_paired[o]);
+    let mut created = Vec::new();
+    for n in new_unpaired.into_iter().filter(|&n| !new_paired[n]) {
+        match old_unpaired.next() {
+            Some(o) => pairs.push((o, n)),
+            None => created.push(n),
+        }
+    }
+    (pairs, old_unpaired.collect(), created)
+}
+
+/// Minimal [`similarity`] of a renamed entry
+const SIMILAR: f64 = 0.5;
+
+/// Dice coefficient of the character pairs, 1 for equal texts and 0 for unrelated ones
+fn similarity(a: &str, b: &str) -> f64 {
+    let bigrams = |text: &str| -> Vec<(char, char)> {
+        let chars: Vec<char> = text.to_lowercase().chars().collect();
+        chars.windows(2).map(|pair| (pair[0], pair[1])).collect()
+    };
+    let (a, mut b) = (bigrams(a), bigrams(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let total = (a.len() + b.len()) as f64;
+    let mut common = 0;
+    for pair in a {
+        if let Some(idx) = b.iter().position(|other| *other == pair) {
+            b.swap_remove(idx);
+            common += 1;
         }
     }
+    2.0 * common as f64 / total
 }
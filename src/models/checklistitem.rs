@@ -1,10 +1,11 @@
 use serde_derive::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ChecklistItem {
-    id: u32,
-    text: String,
-    checked: bool
+    #[serde(skip_serializing)]
+    pub id: u32,
+    pub text: String,
+    pub checked: bool
 }
 
 impl std::fmt::Display for ChecklistItem {
@@ -15,20 +16,30 @@ impl std::fmt::Display for ChecklistItem {
 }
 
 impl ChecklistItem {
-    pub fn from_string(raw_text: String) -> Self {
+    pub fn new(text: String, checked: bool) -> Self {
+        Self {
+            id: 0,
+            text,
+            checked
+        }
+    }
+    pub fn from_string(raw_text: String) -> Result<Self, String> {
         let text = raw_text.trim();
         let text = text.replace("[]", "[ ]");
-        let check = match &text[0..3] {
-            "[ ]" => false,
-            "[x]" => true,
-            _ => panic!("Text will be start with [ ] or [x]")
+        let (check, rest) = if let Some(rest) = text.strip_prefix("[ ]") {
+            (false, rest)
+        } else if let Some(rest) = text.strip_prefix("[x]").or(text.strip_prefix("[X]")) {
+            (true, rest)
+        } else {
+            return Err(format!("Checklist item `{}` must start with [ ] or [x]", text));
         };
-        Self {
+        if rest.trim().is_empty() {
+            return Err(format!("Checklist item `{}` has no text", text));
+        }
+        Ok(Self {
             id: 0,
-            text: if text.len()> 4 {text[4..].to_string()} else {"".to_string()},
+            text: rest.trim().to_string(),
             checked: check
-        }
+        })
     }
 }
-
-
@@ -19,6 +19,12 @@ impl std::fmt::Display for Column {
     }
 }
 
+impl Default for Column {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Column {
     pub fn new() -> Column {
         Column {
@@ -30,7 +36,8 @@ impl Column {
             sort_order: 0.0,
         }
     }
-    fn dispay_subcolumns(subcolumns: &Option<Vec<Column>>) -> String {
+    /// Subcolumns in board order as `title-id`
+    pub fn dispay_subcolumns(subcolumns: &Option<Vec<Column>>) -> String {
         match subcolumns {
             Some(subcolumns) => {
                 let mut scols = subcolumns.clone();
@@ -39,9 +46,9 @@ impl Column {
                     .iter()
                     .map(|c|  format!("{}-{}", c.title, c.id))
                     .collect();
-                format!("{}", str_cols.join(", "))
+                str_cols.join(", ")
             }
-            None => format!(""),
+            None => String::new(),
         }
     }
 
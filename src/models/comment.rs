@@ -4,15 +4,12 @@ use crate::models::User;
 
 #[derive(Debug, Serialize, Deserialize, Tabled)]
 pub struct Comment {
-    #[serde(skip_serializing)]
+    #[serde(default, skip_serializing)]
     pub created: String,
     #[serde(skip_serializing)]
     id: u32,
     text: String,
-    #[tabled(skip)]
-    #[serde(skip_serializing)]
-    edited: bool,
-    #[serde(skip_serializing)]
+    #[serde(default, skip_serializing)]
     author: User,
 }
 impl std::fmt::Display for Comment {
@@ -27,8 +24,16 @@ impl Comment {
             created: String::new(),
             id: 0,
             text: text.to_string(),
-            edited: false,
             author: User::default()
         }
     }
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+    pub fn get_text(&self) -> &str {
+        &self.text
+    }
+    pub fn get_author(&self) -> &User {
+        &self.author
+    }
 }
@@ -1,17 +1,16 @@
-use super::{Board, CardType, Column, Config, Space, Tag, User, Lane};
-use lazy_static::lazy_static;
-use once_cell::sync::OnceCell;
+use super::{Board, CardType, Column, Space, Tag, User, Lane};
 use serde_derive::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::env;
 use std::fs;
 use std::io;
-use std::path::Path;
-use std::sync::Mutex;
+use std::path::PathBuf;
 
-lazy_static! {
-    pub static ref INFO: OnceCell<Info> = OnceCell::new();
-    pub static ref CONFIG: Mutex<Config> = Mutex::new(Config::new());
+/// `~/.config/kaiten-cli`, `HOME` is read when the CLI runs, not when it is built
+pub fn config_dir() -> io::Result<PathBuf> {
+    let home = env::var_os("HOME")
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "HOME is not set"))?;
+    Ok(PathBuf::from(home).join(".config").join("kaiten-cli"))
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -36,9 +35,17 @@ impl Info {
             card_types,
         }
     }
-    pub fn load() -> Result<Self, io::Error> {
-        let file = format!("{}/.config/kaiten-cli/entities.yaml", env!["HOME"]);
-        let file_path = Path::new(file.as_str());
+    /// Cache file written by `kaiten-cli init`, every profile has its own
+    pub fn path(profile: Option<&str>) -> io::Result<PathBuf> {
+        let file = match profile {
+            Some(profile) => format!("entities-{}.yaml", profile),
+            None => "entities.yaml".to_string(),
+        };
+        Ok(config_dir()?.join(file))
+    }
+    pub fn load(profile: Option<&str>) -> Result<Self, io::Error> {
+        let file_path = Self::path(profile)?;
+        let file_path = file_path.as_path();
         if !file_path.exists() {
             return Err(io::Error::new(
                 io::ErrorKind::NotFound,
@@ -60,31 +67,20 @@ impl Info {
         Ok(info)
     }
 
-    pub fn save(&self) -> io::Result<()> {
+    pub fn save(&self, profile: Option<&str>) -> io::Result<()> {
         let content = serde_yaml::to_string(self).map_err(|err| {
             io::Error::new(
                 io::ErrorKind::InvalidData,
                 format!("Failed to serialize data: {}", err),
             )
         })?;
-        let file = format!("{}/.config/kaiten-cli/entities.yaml", env!["HOME"]);
-        let file_path = Path::new(file.as_str());
+        let file_path = Self::path(profile)?;
         if !file_path.parent().unwrap().exists() {
             fs::create_dir_all(file_path.parent().unwrap())?;
         }
         fs::write(file_path, content)?;
         Ok(())
     }
-    pub fn init_global() {
-        match Info::load() {
-            Ok(info) => {
-                INFO.set(info).expect("Failed to initialize global Info");
-            }
-            Err(err) => {
-                eprintln!("Err: {}", err);
-            }
-        }
-    }
     pub fn get_spaces(&self) -> Vec<Space> {
         self.spaces.values().cloned().collect()
     }
@@ -121,9 +117,64 @@ impl Info {
         lanes
     }
 
+    pub fn get_board(&self, board_id: u32) -> Option<Board> {
+        self.boards.get(&board_id).cloned()
+    }
+
+    /// Find board by id or title, case-insensitive.
+    /// A title of several boards is an error, the board could be picked at random
+    pub fn find_board(&self, board: &str) -> Result<Board, String> {
+        let board = board.trim();
+        if let Ok(board_id) = board.parse::<u32>() {
+            return self
+                .get_board(board_id)
+                .ok_or(format!("Board with id {} not found", board_id));
+        }
+        let mut found: Vec<&Board> = self
+            .boards
+            .values()
+            .filter(|b| eq_ignore_case(b.get_title(), board))
+            .collect();
+        found.sort_by_key(|b| b.get_id());
+        match found.as_slice() {
+            [found] => Ok((*found).clone()),
+            [] => Err(format!("Board `{}` not found", board)),
+            _ => {
+                let ids: Vec<String> = found.iter().map(|b| b.get_id().to_string()).collect();
+                Err(format!(
+                    "Ambiguous board `{}`, use one of the ids: {}",
+                    board,
+                    ids.join(", ")
+                ))
+            }
+        }
+    }
+
+    /// Find card type by letter or name
+    pub fn find_card_type(&self, card_type: &str) -> Option<CardType> {
+        let card_type = card_type.trim();
+        let card_types = self.get_card_types();
+        card_types
+            .iter()
+            .find(|t| t.get_letter() == card_type)
+            .or_else(|| {
+                card_types
+                    .iter()
+                    .find(|t| eq_ignore_case(t.get_name(), card_type))
+            })
+            .cloned()
+    }
+
     pub fn get_tags(&self) -> &Vec<Tag> {
         &self.tags
     }
+    /// Find tag by name, case-insensitive
+    pub fn find_tag(&self, name: &str) -> Option<Tag> {
+        self.tags
+            .iter()
+            .find(|t| eq_ignore_case(t.get_name(), name.trim()))
+            .cloned()
+    }
     pub fn get_card_types(&self) -> &Vec<CardType> {
         &self.card_types
     }
@@ -151,10 +202,97 @@ impl Info {
     }
 
     pub fn get_space_id_by_board_id(&self, board_id: u32) -> Option<u32> {
-        match self.boards.get(&board_id) {
-            Some(board) => Some(board.get_space_id()),
-            None => None,
+        self.boards.get(&board_id).map(|board| board.get_space_id())
+    }
+
+    /// Find column or subcolumn by id across all boards
+    pub fn get_column(&self, column_id: u32) -> Option<Column> {
+        self.get_columns(None)
+            .into_iter()
+            .flat_map(|col| {
+                let subcolumns = col.subcolumns.clone().unwrap_or_default();
+                std::iter::once(col).chain(subcolumns)
+            })
+            .find(|col| col.get_id() == column_id)
+    }
+
+    /// Find lane by id across all boards
+    pub fn get_lane(&self, lane_id: u32) -> Option<Lane> {
+        self.get_lanes(None)
+            .into_iter()
+            .find(|lane| lane.get_id() == lane_id)
+    }
+
+    /// Resolve column by id or name (`Parent/Child` for subcolumns) on the board,
+    /// `id:N` picks the column on any board
+    pub fn resolve_column(&self, column: &str, board_id: Option<u32>) -> Result<Column, String> {
+        self.resolve(
+            "column",
+            column,
+            board_id,
+            Board::column_candidates,
+            |id| self.get_column(id).map(|col| (col.get_board_id(), col)),
+        )
+    }
+
+    /// Resolve lane by id or name on the board, `id:N` picks the lane on any board
+    pub fn resolve_lane(&self, lane: &str, board_id: Option<u32>) -> Result<Lane, String> {
+        self.resolve(
+            "lane",
+            lane,
+            board_id,
+            Board::lane_candidates,
+            |id| self.get_lane(id).map(|lane| (lane.get_board_id(), lane)),
+        )
+    }
+
+    /// A number is an id on the board, then a name, so `2024` still finds a column titled so.
+    /// Without a board only ids work, `find_any` returns the board id with the entity
+    fn resolve<T>(
+        &self,
+        kind: &str,
+        query: &str,
+        board_id: Option<u32>,
+        candidates: fn(&Board) -> Vec<Candidate<T>>,
+        find_any: impl Fn(u32) -> Option<(u32, T)>,
+    ) -> Result<T, String> {
+        let query = query.trim();
+        let not_found = |id: u32| format!("No {} with id {}", kind, id);
+        if let Some(id) = query.strip_prefix("id:") {
+            let id = id
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| format!("`{}` is not a {} id", query, kind))?;
+            return find_any(id).map(|(_, value)| value).ok_or(not_found(id));
+        }
+        let number = query.parse::<u32>().ok();
+        if let (Some(id), None) = (number, board_id) {
+            return find_any(id).map(|(_, value)| value).ok_or(not_found(id));
+        }
+        let board = self.board_for_names(board_id, kind, query)?;
+        let mut candidates = candidates(board);
+        if let Some(idx) = number.and_then(|id| candidates.iter().position(|c| c.id == id)) {
+            return Ok(candidates.remove(idx).value);
         }
+        find_by_name(kind, query, candidates).map_err(|err| {
+            match number.and_then(&find_any) {
+                Some((other_board_id, _)) => format!(
+                    "{}, {} with id {} is on board {}, use `id:{}` to pick it",
+                    err, kind, query, other_board_id, query
+                ),
+                None => err,
+            }
+        })
+    }
+
+    fn board_for_names(&self, board_id: Option<u32>, kind: &str, query: &str) -> Result<&Board, String> {
+        let board_id = board_id.ok_or(format!(
+            "Board is not set, use {} id instead of `{}` or `kaiten-cli config set --board-id`",
+            kind, query
+        ))?;
+        self.boards
+            .get(&board_id)
+            .ok_or(format!("Board with id {} not found", board_id))
     }
 
     pub fn get_board_id_by_column_id(&self, column_id: u32) -> Option<u32> {
@@ -165,3 +303,81 @@ impl Info {
         board_id
     }
 }
+
+/// Entity with the name it can be referenced by
+pub struct Candidate<T> {
+    /// Full unique name, e.g. `Parent/Child` for subcolumns
+    pub label: String,
+    title: String,
+    pub id: u32,
+    pub value: T,
+}
+
+impl<T> Candidate<T> {
+    pub fn new(label: String, title: String, id: u32, value: T) -> Self {
+        Self {
+            label,
+            title,
+            id,
+            value,
+        }
+    }
+}
+
+/// Case-insensitive comparison which also works for non-ASCII titles
+pub fn eq_ignore_case(a: &str, b: &str) -> bool {
+    a.to_lowercase() == b.to_lowercase()
+}
+
+/// `Parent/Child` queries match label segment by segment, plain ones match the title
+fn is_prefix<T>(query: &str, candidate: &Candidate<T>) -> bool {
+    if !query.contains('/') {
+        return candidate.title.to_lowercase().starts_with(query);
+    }
+    let label = candidate.label.to_lowercase();
+    let segments: Vec<&str> = label.split('/').collect();
+    let query: Vec<&str> = query.split('/').collect();
+    segments.len() == query.len()
+        && segments
+            .iter()
+            .zip(query)
+            .all(|(segment, query)| segment.starts_with(query.trim()))
+}
+
+/// Pick the candidate by exact label, exact title or unique prefix, case-insensitive
+pub fn find_by_name<T>(kind: &str, query: &str, candidates: Vec<Candidate<T>>) -> Result<T, String> {
+    let name = query.trim();
+    let query = name.to_lowercase();
+    let labels = |found: &[Candidate<T>]| {
+        found
+            .iter()
+            .map(|c| format!("{} ({})", c.label, c.id))
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
+    if let Some(idx) = candidates.iter().position(|c| c.label.to_lowercase() == query) {
+        return Ok(candidates.into_iter().nth(idx).unwrap().value);
+    }
+    let (exact, rest): (Vec<Candidate<T>>, Vec<Candidate<T>>) = candidates
+        .into_iter()
+        .partition(|c| c.title.to_lowercase() == query);
+    let all_labels = labels(&rest);
+    let mut found = if exact.is_empty() {
+        rest.into_iter()
+            .filter(|c| is_prefix(&query, c))
+            .collect()
+    } else {
+        exact
+    };
+    match found.len() {
+        1 => Ok(found.remove(0).value),
+        0 => Err(format!(
+            "No {} matches `{}`. Available: {}",
+            kind, name, all_labels
+        )),
+        _ => Err(format!(
+            "Ambiguous {} `{}`, use one of: {}",
+            kind, name, labels(&found)
+        )),
+    }
+}
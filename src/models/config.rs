@@ -1,17 +1,231 @@
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
 use std::fs;
 use std::io;
 use std::env;
 use serde_derive::{Deserialize, Serialize};
-use super::common::CONFIG;
+use super::card_filter::{CardFilter, IdList};
+use super::common::config_dir;
+use super::profile::Profile;
+use crate::error::Error;
+use tabled::Tabled;
+
+/// Per-repository config, found in the current directory or one of its parents
+pub const LOCAL_CONFIG_FILE: &str = ".kaiten.yaml";
+
+/// Keys of the filters in the order of `config show --origin`
+const FILTER_KEYS: [&str; 5] = [
+    "space_id",
+    "board_id",
+    "exclude_board_ids",
+    "exclude_lane_ids",
+    "exclude_column_ids",
+];
+
+/// Where an effective config value comes from, lowest precedence first
+#[derive(Debug, Clone, PartialEq)]
+pub enum Origin {
+    Default,
+    /// Global `config.yaml`
+    Global(PathBuf),
+    /// Profile of the global `config.yaml`
+    Profile(String),
+    /// `.kaiten.yaml` of the repository
+    Local(PathBuf),
+    /// Environment variable
+    Env(String),
+    /// Command line flag
+    Flag(String),
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Origin::Default => write!(f, "default"),
+            Origin::Global(path) | Origin::Local(path) => write!(f, "{}", path.display()),
+            Origin::Profile(name) => write!(f, "profile {}", name),
+            Origin::Env(name) => write!(f, "env {}", name),
+            Origin::Flag(name) => write!(f, "flag {}", name),
+        }
+    }
+}
+
+/// Filter values of one config layer, `None` keeps the value of the layer below
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Filters {
+    pub space_id: Option<u32>,
+    pub board_id: Option<u32>,
+    pub exclude_board_ids: Option<IdList>,
+    pub exclude_lane_ids: Option<IdList>,
+    pub exclude_column_ids: Option<IdList>,
+}
+
+impl Filters {
+    /// `KAITEN_SPACE_ID`, `KAITEN_BOARD_ID` and `KAITEN_EXCLUDE_*_IDS`,
+    /// plus the older `SPACE_ID` and `BOARD_ID`. Returns the variable of each value
+    fn from_env() -> Result<(Self, BTreeMap<&'static str, String>), String> {
+        fn var<T: std::str::FromStr>(
+            names: &[&str],
+        ) -> Result<Option<(T, String)>, String> {
+            for name in names {
+                if let Ok(value) = env::var(name) {
+                    let parsed = value.trim().parse().map_err(|_| {
+                        format!("Environment variable `{}` is not valid: `{}`", name, value)
+                    })?;
+                    return Ok(Some((parsed, name.to_string())));
+                }
+            }
+            Ok(None)
+        }
+        let mut filters = Self::default();
+        let mut names = BTreeMap::new();
+        if let Some((value, name)) = var(&["KAITEN_SPACE_ID", "SPACE_ID"])? {
+            filters.space_id = Some(value);
+            names.insert("space_id", name);
+        }
+        if let Some((value, name)) = var(&["KAITEN_BOARD_ID", "BOARD_ID"])? {
+            filters.board_id = Some(value);
+            names.insert("board_id", name);
+        }
+        if let Some((value, name)) = var(&["KAITEN_EXCLUDE_BOARD_IDS"])? {
+            filters.exclude_board_ids = Some(value);
+            names.insert("exclude_board_ids", name);
+        }
+        if let Some((value, name)) = var(&["KAITEN_EXCLUDE_LANE_IDS"])? {
+            filters.exclude_lane_ids = Some(value);
+            names.insert("exclude_lane_ids", name);
+        }
+        if let Some((value, name)) = var(&["KAITEN_EXCLUDE_COLUMN_IDS"])? {
+            filters.exclude_column_ids = Some(value);
+            names.insert("exclude_column_ids", name);
+        }
+        Ok((filters, names))
+    }
+
+    fn is_set(&self, key: &str) -> bool {
+        match key {
+            "space_id" => self.space_id.is_some(),
+            "board_id" => self.board_id.is_some(),
+            "exclude_board_ids" => self.exclude_board_ids.is_some(),
+            "exclude_lane_ids" => self.exclude_lane_ids.is_some(),
+            "exclude_column_ids" => self.exclude_column_ids.is_some(),
+            _ => false,
+        }
+    }
+}
+
+/// `.kaiten.yaml` of a repository: the filters and optionally the profile of its board
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct LocalConfig {
+    pub profile: Option<String>,
+    // not a flattened `Filters`, serde_yaml loses the line of the errors in flattened fields
+    pub space_id: Option<u32>,
+    pub board_id: Option<u32>,
+    pub exclude_board_ids: Option<IdList>,
+    pub exclude_lane_ids: Option<IdList>,
+    pub exclude_column_ids: Option<IdList>,
+}
+
+impl LocalConfig {
+    /// The nearest `.kaiten.yaml` in `dir` or its parents, with its path
+    pub fn discover(dir: &Path) -> Result<Option<(PathBuf, Self)>, Error> {
+        let Some(file_path) = dir
+            .ancestors()
+            .map(|dir| dir.join(LOCAL_CONFIG_FILE))
+            .find(|file_path| file_path.is_file())
+        else {
+            return Ok(None);
+        };
+        // an empty file is a valid config without values
+        let local = read_yaml::<Option<Self>>(&file_path)?.unwrap_or_default();
+        Ok(Some((file_path, local)))
+    }
+
+    pub fn filters(&self) -> Filters {
+        Filters {
+            space_id: self.space_id,
+            board_id: self.board_id,
+            exclude_board_ids: self.exclude_board_ids.clone(),
+            exclude_lane_ids: self.exclude_lane_ids.clone(),
+            exclude_column_ids: self.exclude_column_ids.clone(),
+        }
+    }
+}
+
+/// Parse a config file, the error starts with `path:line:column`
+fn read_yaml<T: serde::de::DeserializeOwned>(file_path: &Path) -> Result<T, Error> {
+    let file_error = |message: String| Error::ConfigFile {
+        path: file_path.to_path_buf(),
+        message,
+    };
+    let content = fs::read_to_string(file_path)
+        .map_err(|err| file_error(format!("{}: {}", file_path.display(), err)))?;
+    serde_yaml::from_str(&content).map_err(|err| {
+        let message = err.to_string();
+        // the location is moved to the front, like compiler errors
+        let message = message.split(" at line ").next().unwrap_or_default();
+        file_error(match err.location() {
+            Some(location) => format!(
+                "{}:{}:{}: {}",
+                file_path.display(),
+                location.line(),
+                location.column(),
+                message
+            ),
+            None => format!("{}: {}", file_path.display(), message),
+        })
+    })
+}
+
+/// Values of one run on top of the config files
+#[derive(Debug, Default)]
+pub struct RunOptions {
+    /// `--profile`
+    pub profile: Option<String>,
+    /// `--space-id`
+    pub space_id: Option<u32>,
+    /// `--board-id`
+    pub board_id: Option<u32>,
+    /// `--ignore-config` skips `config.yaml` and `.kaiten.yaml` filters
+    pub ignore_config: bool,
+    /// A selected profile which does not exist is only a warning,
+    /// for the commands which add profiles or log in
+    pub allow_missing_profile: bool,
+}
+
+/// Row of `config show --origin`
+#[derive(Debug, Tabled)]
+pub struct OriginRow {
+    key: String,
+    value: String,
+    origin: String,
+}
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Config {
     space_id: Option<u32>,
     board_id: Option<u32>,
-    exclude_board_ids: Option<String>,
-    exclude_lane_ids: Option<String>,
-    exclude_column_ids: Option<String>,
+    exclude_board_ids: Option<IdList>,
+    exclude_lane_ids: Option<IdList>,
+    exclude_column_ids: Option<IdList>,
+    /// Card filters saved with `cards ls --save NAME`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    queries: BTreeMap<String, CardFilter>,
+    /// Current profile, set with `config profiles use` and replaced by `--profile` for one run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    profile: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    profiles: BTreeMap<String, Profile>,
+    /// Layer of each value which is not a default, see [`Config::layered`]
+    #[serde(skip)]
+    origins: BTreeMap<&'static str, Origin>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Config {
@@ -22,26 +236,148 @@ impl Config {
             exclude_board_ids: None,
             exclude_lane_ids: None,
             exclude_column_ids: None,
+            queries: BTreeMap::new(),
+            profile: None,
+            profiles: BTreeMap::new(),
+            origins: BTreeMap::new(),
         }
     }
-    pub fn load() -> Result<Option<Self>, io::Error> {
-        let file = format!("{}/.config/kaiten-cli/config.yaml", env!["HOME"]);
-        let file_path = Path::new(file.as_str());
+    pub fn path() -> io::Result<PathBuf> {
+        Ok(config_dir()?.join("config.yaml"))
+    }
+    pub fn load() -> Result<Option<Self>, Error> {
+        let file_path = Self::path()?;
+        let file_path = file_path.as_path();
         if !file_path.exists() {
             return Ok(None)
         }
-        let content = fs::read_to_string(file_path)?;
-
-        let info: Self = serde_yaml::from_str(&content).map_err(|err| {
-            io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!("Failed to deserialize {:?}: {}", file_path, err),
-            )
-        })?;
+        let mut info: Self = read_yaml(file_path)?;
+        let filters = info.filters();
+        info.set_origins(&filters, |_| Origin::Global(file_path.to_path_buf()));
+        if info.profile.is_some() {
+            info.origins.insert("profile", Origin::Global(file_path.to_path_buf()));
+        }
 
         Ok(Some(info))
     }
 
+    /// Effective config of one run, each layer overrides the values set in the one below:
+    /// defaults, `config.yaml` (or its profile), `.kaiten.yaml` found from `dir` upwards,
+    /// `KAITEN_*` environment variables, then the flags of `options`.
+    /// The profile is `--profile`, `KAITEN_PROFILE`, the one of `.kaiten.yaml` or the current one
+    pub fn layered(dir: &Path, options: &RunOptions) -> Result<Self, Error> {
+        let mut config = Self::load()?.unwrap_or_else(Self::new);
+        let local = LocalConfig::discover(dir)?;
+        let profile = if let Some(profile) = &options.profile {
+            Some((profile.to_owned(), Origin::Flag("--profile".to_string())))
+        } else if let Ok(profile) = env::var("KAITEN_PROFILE") {
+            Some((profile, Origin::Env("KAITEN_PROFILE".to_string())))
+        } else {
+            local.as_ref().and_then(|(file_path, local)| {
+                local
+                    .profile
+                    .clone()
+                    .map(|profile| (profile, Origin::Local(file_path.to_owned())))
+            })
+        };
+        let used = config.use_profile(profile.as_ref().map(|(name, _)| name.as_str()));
+        match (used, profile) {
+            (Ok(()), Some((_, origin))) => {
+                config.origins.insert("profile", origin);
+            }
+            (Ok(()), None) => {}
+            (Err(err), _) if options.allow_missing_profile => {
+                log::warn!("{}, the top-level config is used", err);
+                config.profile = None;
+            }
+            (Err(err), _) => return Err(Error::Config(err)),
+        }
+        if options.ignore_config {
+            config.reset();
+        } else if let Some((file_path, local)) = local {
+            config.apply(&local.filters(), |_| Origin::Local(file_path.to_owned()));
+        }
+        let (env_filters, names) = Filters::from_env().map_err(Error::Config)?;
+        config.apply(&env_filters, |key| Origin::Env(names[key].to_owned()));
+        let flags = Filters {
+            space_id: options.space_id,
+            board_id: options.board_id,
+            ..Filters::default()
+        };
+        config.apply(&flags, |key| Origin::Flag(format!("--{}", key.replace('_', "-"))));
+        Ok(config)
+    }
+
+    fn filters(&self) -> Filters {
+        Filters {
+            space_id: self.space_id,
+            board_id: self.board_id,
+            exclude_board_ids: self.exclude_board_ids.clone(),
+            exclude_lane_ids: self.exclude_lane_ids.clone(),
+            exclude_column_ids: self.exclude_column_ids.clone(),
+        }
+    }
+
+    fn set_origins(&mut self, filters: &Filters, origin: impl Fn(&str) -> Origin) {
+        for key in FILTER_KEYS {
+            if filters.is_set(key) {
+                self.origins.insert(key, origin(key));
+            } else {
+                self.origins.remove(key);
+            }
+        }
+    }
+
+    /// Override the values which are set in `filters`
+    fn apply(&mut self, filters: &Filters, origin: impl Fn(&str) -> Origin) {
+        for key in FILTER_KEYS {
+            if filters.is_set(key) {
+                self.origins.insert(key, origin(key));
+            }
+        }
+        if let Some(space_id) = filters.space_id {
+            self.space_id = Some(space_id);
+        }
+        if let Some(board_id) = filters.board_id {
+            self.board_id = Some(board_id);
+        }
+        if let Some(exclude_board_ids) = &filters.exclude_board_ids {
+            self.exclude_board_ids = Some(exclude_board_ids.clone());
+        }
+        if let Some(exclude_lane_ids) = &filters.exclude_lane_ids {
+            self.exclude_lane_ids = Some(exclude_lane_ids.clone());
+        }
+        if let Some(exclude_column_ids) = &filters.exclude_column_ids {
+            self.exclude_column_ids = Some(exclude_column_ids.clone());
+        }
+    }
+
+    /// Origin of the effective value of `key`
+    pub fn get_origin(&self, key: &str) -> Origin {
+        self.origins.get(key).cloned().unwrap_or(Origin::Default)
+    }
+
+    /// Effective values with their origins for `config show --origin`
+    pub fn origin_rows(&self) -> Vec<OriginRow> {
+        let display = |value: Option<String>| value.unwrap_or_default();
+        let values = [
+            ("profile", self.profile.clone()),
+            ("space_id", self.space_id.map(|id| id.to_string())),
+            ("board_id", self.board_id.map(|id| id.to_string())),
+            ("exclude_board_ids", self.exclude_board_ids.as_ref().map(IdList::to_string)),
+            ("exclude_lane_ids", self.exclude_lane_ids.as_ref().map(IdList::to_string)),
+            ("exclude_column_ids", self.exclude_column_ids.as_ref().map(IdList::to_string)),
+        ];
+        values
+            .into_iter()
+            .map(|(key, value)| OriginRow {
+                key: key.to_string(),
+                value: display(value),
+                origin: self.get_origin(key).to_string(),
+            })
+            .collect()
+    }
+
     pub fn save(&self) -> io::Result<()> {
         let content = serde_yaml::to_string(self).map_err(|err| {
             io::Error::new(
@@ -49,39 +385,26 @@ impl Config {
                 format!("Failed to serialize data: {}", err),
             )
         })?;
-        let file = format!("{}/.config/kaiten-cli/config.yaml", env!["HOME"]);
-        let file_path = Path::new(file.as_str());
+        let file_path = Self::path()?;
         if !file_path.parent().unwrap().exists() {
             fs::create_dir_all(file_path.parent().unwrap())?;
         }
         fs::write(file_path, content)?;
         Ok(())
     }
-    pub fn init_global() {
-        match Config::load() {
-            Ok(Some(config)) => {
-                *CONFIG.lock().unwrap() = config
-            }
-            Ok(None) => {}
-            Err(err) => {
-                eprintln!("Err: {}", err);
-            }
-        }
-    }
-
     pub fn get_space_id(&self) -> Option<u32> {
         self.space_id
     }
     pub fn get_board_id(&self) -> Option<u32> {
         self.board_id
     }
-    pub fn get_exclude_board_ids(&self) -> Option<&String> {
+    pub fn get_exclude_board_ids(&self) -> Option<&IdList> {
         self.exclude_board_ids.as_ref()
     }
-    pub fn get_exclude_column_ids(&self) -> Option<&String> {
+    pub fn get_exclude_column_ids(&self) -> Option<&IdList> {
         self.exclude_column_ids.as_ref()
     }
-    pub fn get_exclude_lane_ids(&self) -> Option<&String> {
+    pub fn get_exclude_lane_ids(&self) -> Option<&IdList> {
         self.exclude_lane_ids.as_ref()
     }
 
@@ -91,34 +414,97 @@ impl Config {
     pub fn set_board_id(&mut self, board_id: u32) {
         self.board_id = Some(board_id)
     }
-    pub fn set_exclude_board_ids(&mut self, exclude_board_ids: String) {
+    pub fn set_exclude_board_ids(&mut self, exclude_board_ids: IdList) {
         self.exclude_board_ids = Some(exclude_board_ids)
     }
-    pub fn set_exclude_column_ids(&mut self, exclude_column_ids: String) {
+    pub fn set_exclude_column_ids(&mut self, exclude_column_ids: IdList) {
         self.exclude_column_ids = Some(exclude_column_ids)
     }
-    pub fn set_exclude_lane_ids(&mut self, exclude_lane_ids: String) {
+    pub fn set_exclude_lane_ids(&mut self, exclude_lane_ids: IdList) {
         self.exclude_lane_ids = Some(exclude_lane_ids)
     }
 
-    pub fn update(&mut self, space_id: Option<u32>, board_id: Option<u32>) {
-        if let Some(space_id) = space_id {
-            self.set_space_id(space_id)
-        } else if let Ok(space_id) = env::var("SPACE_ID") {
-            if let Ok(space_id) = space_id.parse::<u32>() {
-            self.set_space_id(space_id)
-            }
+    pub fn get_query(&self, name: &str) -> Option<&CardFilter> {
+        self.queries.get(name)
+    }
+    pub fn set_query(&mut self, name: &str, filter: CardFilter) {
+        self.queries.insert(name.to_string(), filter);
+    }
+
+    /// Name of the profile in use
+    pub fn get_profile_name(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+    /// Profile in use, `None` when the top-level values are used
+    pub fn get_profile(&self) -> Option<&Profile> {
+        self.profiles.get(self.profile.as_ref()?)
+    }
+    pub fn get_profiles(&self) -> &BTreeMap<String, Profile> {
+        &self.profiles
+    }
+    pub fn get_profile_mut(&mut self, name: &str) -> Option<&mut Profile> {
+        self.profiles.get_mut(name)
+    }
+    pub fn add_profile(&mut self, name: &str, profile: Profile) {
+        self.profiles.insert(name.to_string(), profile);
+    }
+    /// Remove the profile, it stops being current as well
+    pub fn remove_profile(&mut self, name: &str) -> Option<Profile> {
+        if self.profile.as_deref() == Some(name) {
+            self.profile = None;
         }
-        if let Some(board_id) = board_id {
-            self.set_board_id(board_id)
-        } else if let Ok(board_id) = env::var("SPACE_ID") {
-            if let Ok(board_id) = board_id.parse::<u32>() {
-            self.set_board_id(board_id)
-            }
+        self.profiles.remove(name)
+    }
+    pub fn set_current_profile(&mut self, name: Option<String>) {
+        self.profile = name;
+    }
+
+    /// Switch to the filters of the profile `name`, or of the current profile when it is `None`
+    pub fn use_profile(&mut self, name: Option<&str>) -> Result<(), String> {
+        let Some(name) = name.map(str::to_string).or_else(|| self.profile.clone()) else {
+            return Ok(());
+        };
+        let profile = self.profiles.get(&name).ok_or_else(|| {
+            format!(
+                "Profile `{}` not found, add it with `kaiten-cli config profiles add {} --api-url URL`",
+                name, name
+            )
+        })?;
+        self.space_id = profile.space_id;
+        self.board_id = profile.board_id;
+        self.exclude_board_ids = profile.exclude_board_ids.clone();
+        self.exclude_lane_ids = profile.exclude_lane_ids.clone();
+        self.exclude_column_ids = profile.exclude_column_ids.clone();
+        let filters = self.filters();
+        self.set_origins(&filters, |_| Origin::Profile(name.clone()));
+        self.profile = Some(name);
+        Ok(())
+    }
+
+    /// Filters of the config which are sent with every `GET /cards`
+    pub fn to_filter(&self) -> CardFilter {
+        CardFilter {
+            space_id: self.space_id,
+            board_id: self.board_id,
+            exclude_board_ids: self.exclude_board_ids.clone(),
+            exclude_lane_ids: self.exclude_lane_ids.clone(),
+            exclude_column_ids: self.exclude_column_ids.clone(),
+            ..CardFilter::default()
         }
     }
 
+    /// Drop the filters for `--ignore-config`, saved queries are only used by name
+    /// and the profile in use still selects the Kaiten instance
     pub fn reset(&mut self) {
-        *self = Self::new();
+        let mut origins = std::mem::take(&mut self.origins);
+        *self = Self {
+            queries: std::mem::take(&mut self.queries),
+            profile: self.profile.take(),
+            profiles: std::mem::take(&mut self.profiles),
+            ..Self::new()
+        };
+        if let Some(origin) = origins.remove("profile") {
+            self.origins.insert("profile", origin);
+        }
     }
 }
@@ -0,0 +1,147 @@
+use super::common::config_dir;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+/// Key of the credentials used without a profile, not a valid profile name
+pub(crate) const DEFAULT_KEY: &str = "default";
+
+/// Instance and token saved by `kaiten-cli login`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Credentials {
+    pub api_url: String,
+    pub token: String,
+}
+
+impl Credentials {
+    /// Same instance, with or without the trailing slash
+    pub fn is_for(&self, api_url: &str) -> bool {
+        self.api_url.trim_end_matches('/') == api_url.trim_end_matches('/')
+    }
+}
+
+/// `credentials.yaml` next to `config.yaml`, readable only by the user.
+/// It is kept apart from the config, so the config can be shared without the tokens
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct CredentialsStore {
+    #[serde(flatten)]
+    entries: BTreeMap<String, Credentials>,
+}
+
+impl CredentialsStore {
+    pub fn path() -> io::Result<PathBuf> {
+        Ok(config_dir()?.join("credentials.yaml"))
+    }
+
+    /// Empty store when nobody has logged in yet
+    pub fn load() -> io::Result<Self> {
+        let file_path = Self::path()?;
+        if !file_path.exists() {
+            return Ok(Self::default());
+        }
+        warn_if_readable_by_others(&file_path);
+        let content = fs::read_to_string(&file_path)?;
+        serde_yaml::from_str(&content).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Failed to deserialize {:?}: {}", file_path, err),
+            )
+        })
+    }
+
+    /// Write the store with `0600` permissions, the file is removed when it is empty
+    pub fn save(&self) -> io::Result<()> {
+        let file_path = Self::path()?;
+        if self.entries.is_empty() {
+            if file_path.exists() {
+                fs::remove_file(&file_path)?;
+            }
+            return Ok(());
+        }
+        let content = serde_yaml::to_string(self).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Failed to serialize data: {}", err),
+            )
+        })?;
+        if !file_path.parent().unwrap().exists() {
+            fs::create_dir_all(file_path.parent().unwrap())?;
+        }
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+            options.mode(0o600);
+            // `mode` only applies to new files
+            if file_path.exists() {
+                fs::set_permissions(&file_path, fs::Permissions::from_mode(0o600))?;
+            }
+        }
+        options.open(&file_path)?.write_all(content.as_bytes())
+    }
+
+    /// Credentials of the profile, or the default ones without a profile
+    pub fn get(&self, profile: Option<&str>) -> Option<&Credentials> {
+        self.entries.get(profile.unwrap_or(DEFAULT_KEY))
+    }
+
+    pub fn set(&mut self, profile: Option<&str>, credentials: Credentials) {
+        self.entries
+            .insert(profile.unwrap_or(DEFAULT_KEY).to_string(), credentials);
+    }
+
+    pub fn remove(&mut self, profile: Option<&str>) -> Option<Credentials> {
+        self.entries.remove(profile.unwrap_or(DEFAULT_KEY))
+    }
+}
+
+/// API url of `company`, `company.kaiten.ru` or a full url,
+/// `https://company.kaiten.ru/api/latest/` for the first two
+pub fn api_url_from_domain(domain: &str) -> Result<String, String> {
+    let domain = domain.trim().trim_end_matches('/');
+    if domain.contains("://") {
+        let mut url = reqwest::Url::parse(domain)
+            .map_err(|err| format!("`{}` is not a valid url: {}", domain, err))?;
+        if url.path() == "/" {
+            url.set_path("/api/latest/");
+        } else {
+            url.set_path(&format!("{}/", url.path()));
+        }
+        return Ok(url.to_string());
+    }
+    let valid = !domain.is_empty()
+        && domain
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.' || c == ':');
+    if !valid {
+        return Err(format!(
+            "`{}` is not a Kaiten domain, use `company`, `company.kaiten.ru` or the API url",
+            domain
+        ));
+    }
+    if domain.contains('.') {
+        Ok(format!("https://{}/api/latest/", domain))
+    } else {
+        Ok(format!("https://{}.kaiten.ru/api/latest/", domain))
+    }
+}
+
+#[cfg(unix)]
+fn warn_if_readable_by_others(file_path: &std::path::Path) {
+    use std::os::unix::fs::PermissionsExt;
+    if let Ok(metadata) = fs::metadata(file_path) {
+        if metadata.permissions().mode() & 0o077 != 0 {
+            log::warn!(
+                "{:?} can be read by other users, run `chmod 600 {:?}`",
+                file_path,
+                file_path
+            );
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn warn_if_readable_by_others(_file_path: &std::path::Path) {}
@@ -0,0 +1,90 @@
+use chrono::prelude::*;
+use chrono::Duration;
+use colored::Colorize;
+
+/// Parse due date input relative to `today`.
+/// Accepts ISO dates and datetimes, `today`, `tomorrow`, weekday names (`friday`, `fri`),
+/// `+3d` / `+2w` offsets and `clear` which removes the due date (`None`)
+pub fn parse_due_date(input: &str, today: NaiveDate) -> Result<Option<String>, String> {
+    let input = input.trim();
+    let lower = input.to_lowercase();
+    let out_of_range = || format!("Due date `{}` is out of range", input);
+    let date = match lower.as_str() {
+        "clear" | "none" => return Ok(None),
+        "today" => today,
+        "tomorrow" => today.succ_opt().ok_or_else(out_of_range)?,
+        _ => {
+            if DateTime::parse_from_rfc3339(input).is_ok() {
+                return Ok(Some(input.to_string()));
+            }
+            let offset = if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+                return Ok(Some(date.format("%Y-%m-%d").to_string()));
+            } else if let Ok(weekday) = lower.parse::<Weekday>() {
+                let days = (7 + weekday.num_days_from_monday() as i64
+                    - today.weekday().num_days_from_monday() as i64)
+                    % 7;
+                Duration::try_days(days)
+            } else if let Some(offset) = parse_offset(&lower) {
+                offset
+            } else {
+                return Err(format!(
+                    "Invalid due date `{}`. Use YYYY-MM-DD, today, tomorrow, a weekday, +3d, +2w or clear",
+                    input
+                ));
+            };
+            offset
+                .and_then(|offset| today.checked_add_signed(offset))
+                .ok_or_else(out_of_range)?
+        }
+    };
+    Ok(Some(date.format("%Y-%m-%d").to_string()))
+}
+
+/// `+3d`, `-1d`, `+2w`. `None` when the input is not an offset,
+/// `Some(None)` when the offset does not fit in a `Duration`
+fn parse_offset(input: &str) -> Option<Option<Duration>> {
+    let negative = match input.chars().next()? {
+        '+' => false,
+        '-' => true,
+        _ => return None,
+    };
+    let offset = &input[1..];
+    let (count, weeks) = if let Some(days) = offset.strip_suffix('d') {
+        (days, false)
+    } else if let Some(weeks) = offset.strip_suffix('w') {
+        (weeks, true)
+    } else {
+        return None;
+    };
+    if count.is_empty() || !count.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    // more digits than an i64 holds is still an offset, only too large
+    let count = match count.parse::<i64>() {
+        Ok(count) if negative => -count,
+        Ok(count) => count,
+        Err(_) => return Some(None),
+    };
+    Some(if weeks {
+        Duration::try_weeks(count)
+    } else {
+        Duration::try_days(count)
+    })
+}
+
+/// Date part of the due date as returned by the API
+pub fn due_date_day(due_date: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(due_date)
+        .map(|d| d.date_naive())
+        .ok()
+        .or_else(|| NaiveDate::parse_from_str(due_date.get(..10)?, "%Y-%m-%d").ok())
+}
+
+/// Due date as `YYYY-MM-DD`, red when it is before `today` and the card is not done
+pub fn display_due_date(due_date: &str, today: NaiveDate, done: bool) -> String {
+    match due_date_day(due_date) {
+        Some(day) if day < today && !done => day.to_string().red().to_string(),
+        Some(day) => day.to_string(),
+        None => due_date.to_string(),
+    }
+}
@@ -16,6 +16,12 @@ impl std::fmt::Display for Lane {
         write!(f, "{}", self.get_title())
     }
 }
+impl Default for Lane {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Lane {
     pub fn new() -> Lane {
         Lane {
@@ -32,4 +38,7 @@ impl Lane {
     pub fn get_title(&self) -> &str {
         &self.title
     }
+    pub fn get_board_id(&self) -> u32 {
+        self.board_id
+    }
 }
@@ -5,8 +5,10 @@ pub struct Link{
     #[serde(skip_serializing)]
     id: u32,
     #[tabled(display_with="Self::display_option_string")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     url: Option<String>,
     #[tabled(display_with="Self::display_option_string")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     description: Option<String>
 }
 
@@ -19,12 +21,27 @@ impl Link {
         }
 
     }
+    /// Link with only the fields to change in `PATCH`
+    pub fn patch(url: Option<String>, description: Option<String>) -> Self {
+        Link {
+            id: 0,
+            url,
+            description
+        }
+    }
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+    pub fn get_url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+    pub fn get_description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
     fn display_option_string(s: &Option<String>) -> String {
         match s {
-            Some(s) => {
-                format!("{}", s)
-            }
-            None => format!(""),
+            Some(s) => s.to_string(),
+            None => String::new(),
         }
     }
 }
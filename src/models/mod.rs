@@ -1,30 +1,44 @@
-pub use blocker::Blocker;
+pub use blocker::{BlockedCard, Blocker, NewBlocker};
 pub use board::Board;
 pub use card::{Card, RelatedCard};
+pub use card_tree::CardTree;
 pub use card_type::CardType;
+pub use card_filter::{CardFilter, IdList};
+pub use card_patch::CardPatch;
+pub use card_template::{CardEditTemplate, CardFields, CardTemplate};
 pub use column::Column;
 pub use comment::Comment;
 pub use link::Link;
 pub use space::Space;
 pub use tag::Tag;
-pub use user::User;
+pub use user::{CurrentUser, User};
 pub use lane::Lane;
-pub use checklist::Checklist;
+pub use checklist::{Checklist, ChecklistChange, ChecklistRow, ItemRef};
 pub use checklistitem::ChecklistItem;
 pub use common::Info;
-pub use config::Config;
+pub use config::{Config, Filters, LocalConfig, Origin, OriginRow, RunOptions};
+pub use credentials::{Credentials, CredentialsStore};
+pub use profile::{Profile, ProfileRow};
 
 mod blocker;
 mod board;
 mod card;
+mod card_tree;
 mod card_type;
+mod card_filter;
+mod card_patch;
+mod card_template;
 mod column;
 mod comment;
+pub mod due_date;
 mod link;
 mod space;
 mod config;
+pub mod credentials;
+pub mod profile;
 mod tag;
 mod user;
+pub mod view;
 mod checklist;
 mod checklistitem;
 mod lane;
@@ -0,0 +1,85 @@
+use super::card_filter::IdList;
+use super::credentials::DEFAULT_KEY;
+use serde_derive::{Deserialize, Serialize};
+use tabled::Tabled;
+
+/// Token variable of the profiles without `token_env`
+pub const DEFAULT_TOKEN_ENV: &str = "KT";
+
+/// Kaiten instance with its own filters and entities cache, selected with `--profile`
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Profile {
+    pub api_url: String,
+    /// Environment variable with the token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_env: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub space_id: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub board_id: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude_board_ids: Option<IdList>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude_lane_ids: Option<IdList>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude_column_ids: Option<IdList>,
+}
+
+impl Profile {
+    pub fn get_token_env(&self) -> &str {
+        self.token_env.as_deref().unwrap_or(DEFAULT_TOKEN_ENV)
+    }
+}
+
+/// Profile names are used in cache file names and as keys of `credentials.yaml`,
+/// where `default` is taken by the login without a profile
+pub fn validate_profile_name(name: &str) -> Result<String, String> {
+    if name == DEFAULT_KEY {
+        return Err(format!(
+            "`{}` is reserved for the login without a profile, choose another name",
+            name
+        ));
+    }
+    if !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        Ok(name.to_string())
+    } else {
+        Err(format!(
+            "`{}` is not a valid profile name, use letters, digits, `-` and `_`",
+            name
+        ))
+    }
+}
+
+/// Row of `config profiles ls`
+#[derive(Debug, Tabled)]
+pub struct ProfileRow {
+    name: String,
+    api_url: String,
+    token: String,
+    #[tabled(display_with = "display_option")]
+    space_id: Option<u32>,
+    #[tabled(display_with = "display_option")]
+    board_id: Option<u32>,
+    current: String,
+}
+
+impl ProfileRow {
+    pub fn from(name: &str, profile: &Profile, current: bool) -> Self {
+        ProfileRow {
+            name: name.to_string(),
+            api_url: profile.api_url.clone(),
+            token: format!("${}", profile.get_token_env()),
+            space_id: profile.space_id,
+            board_id: profile.board_id,
+            current: if current { "yes".to_string() } else { String::new() },
+        }
+    }
+}
+
+fn display_option(value: &Option<u32>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
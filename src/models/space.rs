@@ -15,6 +15,9 @@ impl Space {
     pub fn get_id(&self) -> u32 {
         self.id
     }
+    pub fn get_title(&self) -> &str {
+        &self.title
+    }
     pub fn get_boards_ids(&self) -> Vec<u32> {
         self.boards
             .as_ref()
@@ -9,6 +9,9 @@ pub struct Tag {
 }
 
 impl Tag {
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
     pub fn get_name(&self) -> &str {
         &self.name
     }
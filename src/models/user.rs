@@ -3,7 +3,8 @@ use serde_derive::{Serialize,Deserialize};
 use tabled::Tabled;
 #[derive(Serialize, Deserialize, Debug, Tabled, Clone)]
 pub struct User{
-    #[serde(rename(serialize="user_id"))]
+    // sent as `user_id` to the members endpoint, read back from the entities cache
+    #[serde(rename(serialize="user_id"), alias = "user_id")]
     id: u32,
     username: String,
     #[tabled(skip)]
@@ -11,6 +12,17 @@ pub struct User{
     r#type: Role
 }
 
+/// Owner of the API token, `GET /users/current`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CurrentUser {
+    pub id: u32,
+    pub username: String,
+    #[serde(default)]
+    pub full_name: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 enum Role {
     Empty,
@@ -28,15 +40,17 @@ impl std::fmt::Display for User {
         write!(f, "{}", self.username)
     }
 }
-impl User {
-
-    pub fn default() -> Self {
+impl Default for User {
+    fn default() -> Self {
         User {
             id: 0,
             username: String::new(),
             r#type: Role::Empty
         }
     }
+}
+
+impl User {
 
     fn deserialize_type<'de, D>(deserializer: D) -> Result<Role, D::Error>
     where
@@ -0,0 +1,267 @@
+//! Stable shapes for `--output json|yaml`.
+//! Fields are only ever added here, so scripts keep working when the API or the models change.
+use super::{
+    Board, Card, CardType, Checklist, Column, Comment, Lane, Link, Space, Tag, User,
+};
+use serde_derive::Serialize;
+
+#[derive(Serialize, Debug)]
+pub struct EntityRef {
+    pub id: u32,
+    pub title: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CardTypeView {
+    pub id: u32,
+    pub letter: String,
+    pub name: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct MemberView {
+    pub id: u32,
+    pub username: String,
+    pub responsible: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ChecklistItemView {
+    pub id: u32,
+    pub text: String,
+    pub checked: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ChecklistView {
+    pub id: Option<u32>,
+    pub name: String,
+    pub items: Vec<ChecklistItemView>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CardView {
+    pub id: u32,
+    pub title: String,
+    pub board_id: u32,
+    pub column: EntityRef,
+    pub lane: EntityRef,
+    #[serde(rename = "type")]
+    pub card_type: CardTypeView,
+    /// 1 - queued, 2 - in progress, 3 - done
+    pub state: u8,
+    pub archived: bool,
+    pub blocked: bool,
+    pub tags: Vec<String>,
+    pub members: Vec<MemberView>,
+    pub responsible: Option<String>,
+    pub due_date: Option<String>,
+    pub description: String,
+    pub checklists: Vec<ChecklistView>,
+    pub created: String,
+    pub updated: String,
+    pub last_moved_at: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ColumnView {
+    pub id: u32,
+    pub title: String,
+    pub board_id: u32,
+    pub subcolumns: Vec<ColumnView>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct LaneView {
+    pub id: u32,
+    pub title: String,
+    pub board_id: u32,
+}
+
+#[derive(Serialize, Debug)]
+pub struct UserView {
+    pub id: u32,
+    pub username: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TagView {
+    pub id: u32,
+    pub name: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SpaceView {
+    pub id: u32,
+    pub title: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct BoardView {
+    pub id: u32,
+    pub title: String,
+    pub space_id: u32,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CommentView {
+    pub id: u32,
+    pub text: String,
+    pub author: String,
+    pub created: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct LinkView {
+    pub id: u32,
+    pub url: Option<String>,
+    pub description: Option<String>,
+}
+
+impl From<&CardType> for CardTypeView {
+    fn from(card_type: &CardType) -> Self {
+        Self {
+            id: card_type.get_id(),
+            letter: card_type.get_letter().to_string(),
+            name: card_type.get_name().to_string(),
+        }
+    }
+}
+
+impl From<&Checklist> for ChecklistView {
+    fn from(checklist: &Checklist) -> Self {
+        Self {
+            id: checklist.id,
+            name: checklist.name.clone(),
+            items: checklist
+                .get_items()
+                .into_iter()
+                .map(|item| ChecklistItemView {
+                    id: item.id,
+                    text: item.text,
+                    checked: item.checked,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&Card> for CardView {
+    fn from(card: &Card) -> Self {
+        Self {
+            id: card.get_id(),
+            title: card.get_title().to_string(),
+            board_id: card.get_board_id(),
+            column: EntityRef {
+                id: card.get_column_id(),
+                title: card.get_column().get_title().to_string(),
+            },
+            lane: EntityRef {
+                id: card.get_lane_id(),
+                title: card.get_lane().get_title().to_string(),
+            },
+            card_type: card.get_type().into(),
+            state: card.get_state(),
+            archived: card.archived,
+            blocked: card.is_blocked(),
+            tags: card.get_tags().iter().map(|t| t.get_name().to_string()).collect(),
+            members: card
+                .get_members()
+                .iter()
+                .map(|m| MemberView {
+                    id: m.get_id(),
+                    username: m.get_username().to_string(),
+                    responsible: m.is_responsible(),
+                })
+                .collect(),
+            responsible: card.get_responsible().map(|u| u.get_username().to_string()),
+            due_date: card.get_due_date().map(|d| d.to_string()),
+            description: card.get_description().to_string(),
+            checklists: card.get_checklists().iter().map(ChecklistView::from).collect(),
+            created: card.get_created().to_string(),
+            updated: card.get_updated().to_string(),
+            last_moved_at: card.get_last_moved_at().to_string(),
+        }
+    }
+}
+
+impl From<&Column> for ColumnView {
+    fn from(column: &Column) -> Self {
+        let mut subcolumns = column.subcolumns.clone().unwrap_or_default();
+        subcolumns.sort_by(|a, b| a.sort_order.partial_cmp(&b.sort_order).unwrap());
+        Self {
+            id: column.get_id(),
+            title: column.get_title().to_string(),
+            board_id: column.get_board_id(),
+            subcolumns: subcolumns.iter().map(ColumnView::from).collect(),
+        }
+    }
+}
+
+impl From<&Lane> for LaneView {
+    fn from(lane: &Lane) -> Self {
+        Self {
+            id: lane.get_id(),
+            title: lane.get_title().to_string(),
+            board_id: lane.get_board_id(),
+        }
+    }
+}
+
+impl From<&User> for UserView {
+    fn from(user: &User) -> Self {
+        Self {
+            id: user.get_id(),
+            username: user.get_username().to_string(),
+        }
+    }
+}
+
+impl From<&Tag> for TagView {
+    fn from(tag: &Tag) -> Self {
+        Self {
+            id: tag.get_id(),
+            name: tag.get_name().to_string(),
+        }
+    }
+}
+
+impl From<&Space> for SpaceView {
+    fn from(space: &Space) -> Self {
+        Self {
+            id: space.get_id(),
+            title: space.get_title().to_string(),
+        }
+    }
+}
+
+impl From<&Board> for BoardView {
+    fn from(board: &Board) -> Self {
+        Self {
+            id: board.get_id(),
+            title: board.get_title().to_string(),
+            space_id: board.get_space_id(),
+        }
+    }
+}
+
+impl From<&Comment> for CommentView {
+    fn from(comment: &Comment) -> Self {
+        Self {
+            id: comment.get_id(),
+            text: comment.get_text().to_string(),
+            author: comment.get_author().get_username().to_string(),
+            created: comment.created.clone(),
+        }
+    }
+}
+
+impl From<&Link> for LinkView {
+    fn from(link: &Link) -> Self {
+        Self {
+            id: link.get_id(),
+            url: link.get_url().map(|u| u.to_string()),
+            description: link.get_description().map(|d| d.to_string()),
+        }
+    }
+}
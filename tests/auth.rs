@@ -0,0 +1,114 @@
+mod common;
+
+use common::{stderr, Cli, MockKaiten, BAD_TOKEN, TOKEN};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::process::Output;
+
+fn login(cli: &Cli, token: &str) -> Output {
+    let command = cli.without_env(&["login", "--domain", cli.mock.url(), "--with-token"]);
+    cli.run_with_stdin(command, &format!("{}\n", token))
+}
+
+fn credentials_file(cli: &Cli) -> std::path::PathBuf {
+    cli.config_dir().join("credentials.yaml")
+}
+
+#[test]
+fn login_saves_token_readable_only_by_user() {
+    let cli = Cli::new();
+    let output = login(&cli, TOKEN);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("as alice"));
+    assert_eq!(cli.mock.requests_to("GET", "users/current").len(), 1);
+
+    let file = credentials_file(&cli);
+    assert_eq!(fs::metadata(&file).unwrap().permissions().mode() & 0o777, 0o600);
+    assert!(fs::read_to_string(&file).unwrap().contains(TOKEN));
+    let config = cli.config_dir().join("config.yaml");
+    assert!(!config.exists() || !fs::read_to_string(config).unwrap().contains(TOKEN));
+}
+
+#[test]
+fn saved_credentials_are_used_without_env() {
+    let cli = Cli::new();
+    assert!(login(&cli, TOKEN).status.success());
+
+    let output = cli.without_env(&["cards", "get", "5"]).output().unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+    let requests = cli.mock.requests_to("GET", "cards/5");
+    assert_eq!(requests[0].authorization, Some(format!("Bearer {}", TOKEN)));
+
+    let output = cli.without_env(&["whoami"]).output().unwrap();
+    let whoami = String::from_utf8(output.stdout).unwrap();
+    assert!(whoami.contains("alice (Alice Smith)"), "{}", whoami);
+    assert!(whoami.contains("credentials.yaml"), "{}", whoami);
+}
+
+#[test]
+fn env_takes_precedence_over_saved_credentials() {
+    let cli = Cli::new();
+    assert!(login(&cli, TOKEN).status.success());
+
+    let whoami = cli.stdout(&["whoami"]);
+    assert!(whoami.contains("environment variable KT"), "{}", whoami);
+
+    let output = cli.command(&["cards", "get", "5"]).env("KT", "env-token").output().unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+    let requests = cli.mock.requests_to("GET", "cards/5");
+    assert_eq!(requests[0].authorization, Some("Bearer env-token".to_string()));
+}
+
+#[test]
+fn saved_token_is_not_sent_to_another_instance() {
+    let cli = Cli::new();
+    assert!(login(&cli, TOKEN).status.success());
+
+    let other = MockKaiten::start();
+    let output = cli
+        .without_env(&["cards", "get", "5"])
+        .env("API_URL", other.url())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert!(other.requests().is_empty());
+}
+
+#[test]
+fn invalid_token_is_not_saved() {
+    let cli = Cli::new();
+    let output = login(&cli, BAD_TOKEN);
+    assert_eq!(output.status.code(), Some(10));
+    assert!(!credentials_file(&cli).exists());
+}
+
+#[test]
+fn logout_removes_credentials() {
+    let cli = Cli::new();
+    assert!(login(&cli, TOKEN).status.success());
+
+    cli.stdout(&["logout"]);
+    assert!(!credentials_file(&cli).exists());
+    let output = cli.without_env(&["whoami"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(cli.run(&["logout"]).status.code(), Some(5));
+}
+
+#[test]
+fn profile_login_is_separate() {
+    let cli = Cli::new();
+    let client = MockKaiten::start();
+    cli.stdout(&[
+        "config", "profiles", "add", "client", "--api-url", client.url(), "--token-env", "CLIENT_KT",
+    ]);
+    let command = cli.without_env(&["--profile", "client", "login", "--with-token"]);
+    let output = cli.run_with_stdin(command, "client-token\n");
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let output = cli.without_env(&["--profile", "client", "cards", "get", "5"]).output().unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+    let requests = client.requests_to("GET", "cards/5");
+    assert_eq!(requests[0].authorization, Some("Bearer client-token".to_string()));
+    // the default instance has no saved token
+    assert_eq!(cli.without_env(&["whoami"]).output().unwrap().status.code(), Some(3));
+}
@@ -0,0 +1,107 @@
+use kaiten_cli::models::{CardFilter, IdList};
+
+fn ids(text: &str) -> Result<Vec<u32>, String> {
+    text.parse::<IdList>().map(|list| list.0)
+}
+
+fn yaml_ids(yaml: &str) -> Result<Vec<u32>, String> {
+    serde_yaml::from_str::<IdList>(yaml)
+        .map(|list| list.0)
+        .map_err(|err| err.to_string())
+}
+
+#[test]
+fn later_layers_win_and_unset_fields_are_kept() {
+    let config = CardFilter {
+        board_id: Some(10),
+        exclude_lane_ids: Some(IdList(vec![201])),
+        ..CardFilter::default()
+    };
+    let saved_query = CardFilter {
+        states: Some(IdList(vec![3])),
+        query: Some("crash".to_string()),
+        asap: Some(true),
+        ..CardFilter::default()
+    };
+    let args = CardFilter {
+        board_id: Some(11),
+        asap: Some(false),
+        ..CardFilter::default()
+    };
+    let filter = CardFilter::not_done()
+        .merge(&config)
+        .merge(&saved_query)
+        .merge(&args);
+    assert_eq!(
+        filter,
+        CardFilter {
+            condition: Some(1),
+            states: Some(IdList(vec![3])),
+            board_id: Some(11),
+            exclude_lane_ids: Some(IdList(vec![201])),
+            query: Some("crash".to_string()),
+            asap: Some(false),
+            ..CardFilter::default()
+        }
+    );
+}
+
+#[test]
+fn exclude_lists_are_joined() {
+    let config = CardFilter {
+        exclude_column_ids: Some(IdList(vec![1, 2])),
+        exclude_lane_ids: Some(IdList(vec![201])),
+        ..CardFilter::default()
+    };
+    let args = CardFilter {
+        exclude_column_ids: Some(IdList(vec![2, 3])),
+        exclude_board_ids: Some(IdList(vec![10])),
+        ..CardFilter::default()
+    };
+    let filter = config.merge(&args);
+    assert_eq!(filter.exclude_column_ids, Some(IdList(vec![1, 2, 3])));
+    assert_eq!(filter.exclude_lane_ids, Some(IdList(vec![201])));
+    assert_eq!(filter.exclude_board_ids, Some(IdList(vec![10])));
+}
+
+#[test]
+fn merging_empty_filter_changes_nothing() {
+    let filter = CardFilter {
+        column_ids: Some(IdList(vec![1, 2])),
+        limit: Some(100),
+        ..CardFilter::not_done()
+    };
+    assert_eq!(filter.clone().merge(&CardFilter::default()), filter);
+    assert_eq!(CardFilter::default().merge(&filter), filter);
+}
+
+#[test]
+fn id_list_from_text() {
+    assert_eq!(ids("1,2,3"), Ok(vec![1, 2, 3]));
+    assert_eq!(ids(" 1 , 2 ,"), Ok(vec![1, 2]));
+    assert_eq!(
+        ids("1,,x"),
+        Err("`x` in `1,,x` is not an id, use comma separated numbers like 1,2,3".to_string())
+    );
+    assert_eq!(
+        ids(""),
+        Err("`` has no ids, use comma separated numbers like 1,2,3".to_string())
+    );
+    assert!(ids(" , ").unwrap_err().contains("has no ids"));
+    assert!(ids("-1").unwrap_err().contains("is not an id"));
+    assert_eq!(IdList(vec![1, 2]).to_string(), "1,2");
+}
+
+#[test]
+fn id_list_from_yaml() {
+    assert_eq!(yaml_ids("'1,2'"), Ok(vec![1, 2]));
+    assert_eq!(yaml_ids("5"), Ok(vec![5]));
+    assert_eq!(yaml_ids("[1, 2]"), Ok(vec![1, 2]));
+    assert_eq!(yaml_ids("- 3\n- 4\n"), Ok(vec![3, 4]));
+    assert!(yaml_ids("-5").unwrap_err().contains("`-5` is not an id"));
+    assert!(yaml_ids("[]").unwrap_err().contains("the list has no ids"));
+    assert!(yaml_ids("[1, x]").is_err());
+    assert!(yaml_ids("'1,,x'").unwrap_err().contains("`x` in `1,,x` is not an id"));
+    let saved = serde_yaml::to_string(&IdList(vec![1, 2])).unwrap();
+    assert_eq!(yaml_ids(&saved), Ok(vec![1, 2]));
+}
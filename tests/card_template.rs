@@ -0,0 +1,162 @@
+mod common;
+
+use common::{fixture, fixture_info};
+use kaiten_cli::models::{Board, CardEditTemplate, CardTemplate, Info, Space};
+use serde_json::json;
+use std::collections::HashMap;
+
+fn resolve(text: &str) -> Result<serde_json::Value, String> {
+    let template = CardTemplate::from_text(text)?;
+    let resolved = template.resolve(&fixture_info(), None)?;
+    Ok(json!({
+        "card": serde_json::to_value(&resolved.card).unwrap(),
+        "tags": resolved.tags,
+        "responsible": resolved.responsible.map(|user| user.get_username().to_string()),
+    }))
+}
+
+#[test]
+fn front_matter_and_description() {
+    let template = CardTemplate::from_text(
+        "\n---\ntitle: Fix login\nboard: Development\ntags: [bug, frontend]\n---\n\nSteps:\n\n1. open\n",
+    )
+    .unwrap();
+    assert_eq!(template.fields.title.as_deref(), Some("Fix login"));
+    assert_eq!(template.fields.board.as_deref(), Some("Development"));
+    assert_eq!(template.tags, vec!["bug", "frontend"]);
+    assert_eq!(template.description, "Steps:\n\n1. open");
+}
+
+#[test]
+fn rendered_template_parses_back() {
+    let board = fixture_info().get_board(10);
+    let text = CardTemplate::new(board.as_ref()).to_text().unwrap();
+    let template = CardTemplate::from_text(&text).unwrap();
+    assert_eq!(template.fields.board.as_deref(), Some("Development"));
+    assert_eq!(template.fields.column.as_deref(), Some("Queue"));
+    assert_eq!(template.fields.lane.as_deref(), Some("Main"));
+    assert_eq!(template.fields.title, None);
+}
+
+#[test]
+fn numbers_and_empty_values() {
+    let template = CardTemplate::from_text("---\ntitle: T\nboard: 10\ncolumn: ''\nlane:\n---\n").unwrap();
+    assert_eq!(template.fields.board.as_deref(), Some("10"));
+    assert_eq!(template.fields.column, None);
+    assert_eq!(template.fields.lane, None);
+}
+
+#[test]
+fn missing_or_broken_front_matter() {
+    for text in ["title: T\n", "---\ntitle: T\n", ""] {
+        let err = CardTemplate::from_text(text).unwrap_err();
+        assert!(err.contains("front-matter block"), "{:?}: {}", text, err);
+    }
+    let err = CardTemplate::from_text("---\ntitle: [T\n---\n").unwrap_err();
+    assert!(err.starts_with("Failed to parse card front-matter"), "{}", err);
+    let err = CardTemplate::from_text("---\ntitle: T\nboard: [1]\n---\n").unwrap_err();
+    assert!(err.contains("expected a string or a number"), "{}", err);
+}
+
+#[test]
+fn resolves_names_into_payload() {
+    let resolved = resolve(
+        "---\ntitle: Fix login\nboard: development\ncolumn: Review\nlane: Mobile\ntype: B\ndue_date: 2024-02-01\ntags: [bug]\nresponsible: alice\n---\nText\n",
+    )
+    .unwrap();
+    assert_eq!(
+        resolved,
+        json!({
+            "card": {
+                "title": "Fix login",
+                "board_id": 10,
+                "column_id": 102,
+                "lane_id": 201,
+                "type_id": 8,
+                "description": "Text",
+                "due_date": "2024-02-01"
+            },
+            "tags": ["bug"],
+            "responsible": "alice"
+        })
+    );
+}
+
+#[test]
+fn board_defaults_to_first_column_and_lane() {
+    let template = CardTemplate::from_text("---\ntitle: T\n---\n").unwrap();
+    let resolved = template.resolve(&fixture_info(), Some(10)).unwrap();
+    let card = serde_json::to_value(&resolved.card).unwrap();
+    assert_eq!(card["column_id"], 100);
+    assert_eq!(card["lane_id"], 200);
+    assert!(card.get("description").is_none());
+}
+
+#[test]
+fn resolve_errors() {
+    let cases = [
+        ("---\nboard: Development\n---\n", "title is required"),
+        ("---\ntitle: T\n---\n", "Board is required"),
+        ("---\ntitle: T\nboard: Marketing\n---\n", "Board `Marketing` not found"),
+        ("---\ntitle: T\nboard: Development\ntype: X\n---\n", "Card type `X` not found"),
+        ("---\ntitle: T\nboard: Development\nresponsible: carol\n---\n", "User `carol` not found"),
+        ("---\ntitle: T\nboard: Development\ndue_date: someday\n---\n", "Invalid due date"),
+    ];
+    for (text, expected) in cases {
+        let err = resolve(text).unwrap_err();
+        assert!(err.contains(expected), "{:?}: {}", text, err);
+    }
+}
+
+/// Fixture board 10 and board 11 with the same title in another case
+fn info_with_same_titles() -> Info {
+    let spaces: Vec<Space> = serde_json::from_value(fixture("spaces.json")).unwrap();
+    let board: Board = serde_json::from_value(fixture("board_10.json")).unwrap();
+    let mut other = fixture("board_10.json");
+    other["id"] = json!(11);
+    other["title"] = json!("DEVELOPMENT");
+    for column in other["columns"].as_array_mut().unwrap() {
+        column["board_id"] = json!(11);
+        column["id"] = json!(column["id"].as_u64().unwrap() + 200);
+        column["subcolumns"] = json!(null);
+    }
+    for lane in other["lanes"].as_array_mut().unwrap() {
+        lane["board_id"] = json!(11);
+        lane["id"] = json!(lane["id"].as_u64().unwrap() + 200);
+    }
+    let other: Board = serde_json::from_value(other).unwrap();
+    Info::from(
+        spaces.into_iter().map(|space| (space.get_id(), space)).collect(),
+        HashMap::from([(board.get_id(), board), (other.get_id(), other)]),
+        Vec::new(),
+        Vec::new(),
+    )
+}
+
+#[test]
+fn shared_board_title_keeps_current_board_or_fails() {
+    let info = info_with_same_titles();
+    let template = CardTemplate::from_text("---\ntitle: T\nboard: Development\n---\n").unwrap();
+    let err = template.fields.resolve(&info, None).unwrap_err();
+    assert_eq!(err, "Ambiguous board `Development`, use one of the ids: 10, 11");
+    for board_id in [10, 11] {
+        let fields = template.fields.resolve(&info, Some(board_id)).unwrap();
+        assert_eq!(fields.board.get_id(), board_id);
+    }
+    let template = CardTemplate::from_text("---\ntitle: T\nboard: 11\n---\n").unwrap();
+    let fields = template.fields.resolve(&info, Some(10)).unwrap();
+    assert_eq!((fields.board.get_id(), fields.column.get_id()), (11, 300));
+}
+
+#[test]
+fn edit_template_keeps_checklists_section() {
+    let text = "---\ntitle: T\n---\nDescription\n\n<!-- checklists: `### name` followed by `[ ] item` / `[x] item` lines -->\n### Release\n[x] build\n[ ] deploy\n";
+    let template = CardEditTemplate::from_text(text).unwrap();
+    assert_eq!(template.description, "Description");
+    let checklists = template.checklists.unwrap();
+    assert_eq!(checklists.len(), 1);
+    assert_eq!(checklists[0].to_string(), "### Release\n[x] build\n[ ] deploy");
+
+    let template = CardEditTemplate::from_text("---\ntitle: T\n---\nOnly text\n").unwrap();
+    assert!(template.checklists.is_none());
+}
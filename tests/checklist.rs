@@ -0,0 +1,218 @@
+use kaiten_cli::models::{
+    CardEditTemplate, Checklist, ChecklistChange, ChecklistItem, ChecklistRow, ItemRef,
+};
+
+fn item(id: u32, text: &str, checked: bool) -> ChecklistItem {
+    ChecklistItem {
+        id,
+        ..ChecklistItem::new(text.to_string(), checked)
+    }
+}
+
+fn checklist(id: u32, name: &str, items: Vec<ChecklistItem>) -> Checklist {
+    Checklist {
+        id: Some(id),
+        name: name.to_string(),
+        items: Some(items),
+    }
+}
+
+#[test]
+fn parses_name_and_items() {
+    let checklist = Checklist::from_text("### Release \n[x] build\n[] tag\n  [ ]   deploy now \n", 1).unwrap();
+    assert_eq!(checklist.name, "Release");
+    let items: Vec<(String, bool)> = checklist
+        .get_items()
+        .into_iter()
+        .map(|item| (item.text, item.checked))
+        .collect();
+    assert_eq!(
+        items,
+        vec![
+            ("build".to_string(), true),
+            ("tag".to_string(), false),
+            ("deploy now".to_string(), false)
+        ]
+    );
+}
+
+#[test]
+fn blank_lines_are_skipped() {
+    let checklist = Checklist::from_text("\n### QA\n\n    \n\t\n[x] ok\n", 1).unwrap();
+    assert_eq!(checklist.get_items().len(), 1);
+    let checklist = Checklist::from_text("### Empty\n", 1).unwrap();
+    assert!(checklist.get_items().is_empty());
+}
+
+#[test]
+fn errors_have_line_numbers() {
+    let cases = [
+        ("### QA\n[x] ok\n\n[x]\n", 10, "Line 13: Checklist item `[x]` has no text"),
+        ("### QA\n[ ]   \n", 1, "Line 2: Checklist item `[ ]` has no text"),
+        ("### QA\n- [x] ok\n", 5, "Line 6: Checklist item `- [x] ok` must start with [ ] or [x]"),
+        ("QA\n[x] ok\n", 1, "Line 1: checklist must start with `### name`"),
+        ("###  \n", 3, "Line 3: checklist must start with `### name`"),
+        ("\n  \n", 4, "Line 4: checklist is empty"),
+    ];
+    for (text, first_line, expected) in cases {
+        let err = Checklist::from_text(text, first_line).unwrap_err();
+        assert!(err.starts_with(expected), "{:?}: {}", text, err);
+    }
+}
+
+#[test]
+fn edit_template_errors_point_to_file_lines() {
+    let text = "---\ntitle: T\n---\nText\n\n<!-- checklists: `### name` followed by `[ ] item` / `[x] item` lines -->\n### Release\n[x] build\n[x]\n";
+    let err = CardEditTemplate::from_text(text).unwrap_err();
+    assert!(err.starts_with("Line 9: "), "{}", err);
+
+    let text = "---\ntitle: T\n---\n\n<!-- checklists: `### name` followed by `[ ] item` / `[x] item` lines -->\n[ ] orphan\n";
+    let err = CardEditTemplate::from_text(text).unwrap_err();
+    assert!(err.starts_with("Line 6: `[ ] orphan` is not in a checklist"), "{}", err);
+}
+
+#[test]
+fn rows_are_found_by_index_or_id() {
+    let checklists = vec![
+        checklist(1, "Release", vec![item(2, "build", true), item(900, "deploy", false)]),
+        checklist(3, "QA", vec![item(1, "smoke", false)]),
+    ];
+    let rows = || ChecklistRow::from_checklists(&checklists);
+    let find = |item| ChecklistRow::find(rows(), item).map(|row| (row.checklist_id, row.item));
+
+    assert_eq!(find(ItemRef::Index(1)), Some((1, "[x] build".to_string())));
+    assert_eq!(find(ItemRef::Index(3)), Some((3, "[ ] smoke".to_string())));
+    assert_eq!(find(ItemRef::Index(4)), None);
+    assert_eq!(find(ItemRef::Index(0)), None);
+    // small ids are not taken for indexes
+    assert_eq!(find(ItemRef::Id(1)), Some((3, "[ ] smoke".to_string())));
+    assert_eq!(find(ItemRef::Id(2)), Some((1, "[x] build".to_string())));
+    assert_eq!(find(ItemRef::Id(900)), Some((1, "[ ] deploy".to_string())));
+    assert_eq!(find(ItemRef::Id(3)), None);
+}
+
+/// Changes as short strings, e.g. `update 2 [x] tag`
+fn item_changes(old: Vec<ChecklistItem>, new: Vec<ChecklistItem>) -> Vec<String> {
+    let changes = Checklist::diff(&[checklist(1, "Release", old)], &[checklist(0, "Release", new)]);
+    changes
+        .into_iter()
+        .map(|change| match change {
+            ChecklistChange::CreateItem { checklist_id: 1, item } => format!("create {}", item),
+            ChecklistChange::UpdateItem { checklist_id: 1, item } => {
+                format!("update {} {}", item.id, item)
+            }
+            ChecklistChange::DeleteItem {
+                checklist_id: 1,
+                item_id,
+            } => format!("delete {}", item_id),
+            other => panic!("unexpected change {:?}", other),
+        })
+        .collect()
+}
+
+fn release_items() -> Vec<ChecklistItem> {
+    vec![
+        item(1, "build artifacts", true),
+        item(2, "deploy to staging", false),
+        item(3, "notify the team", false),
+    ]
+}
+
+#[test]
+fn diff_of_unchanged_or_reordered_items_is_empty() {
+    assert!(item_changes(release_items(), release_items()).is_empty());
+    let mut reordered = release_items();
+    reordered.reverse();
+    assert!(item_changes(release_items(), reordered).is_empty());
+}
+
+#[test]
+fn diff_updates_renamed_and_checked_items() {
+    let mut new = release_items();
+    new[1].text = "deploy to production".to_string();
+    new[2].checked = true;
+    assert_eq!(
+        item_changes(release_items(), new),
+        vec!["update 3 [x] notify the team", "update 2 [ ] deploy to production"]
+    );
+}
+
+#[test]
+fn diff_creates_inserted_and_deletes_removed_items() {
+    let new = vec![
+        item(0, "write changelog", false),
+        item(0, "build artifacts", true),
+        item(0, "notify the team", false),
+    ];
+    assert_eq!(
+        item_changes(release_items(), new),
+        vec!["update 2 [ ] write changelog"]
+    );
+    let new = vec![
+        item(0, "build artifacts", true),
+        item(0, "deploy to staging", false),
+        item(0, "notify the team", false),
+        item(0, "close the sprint", false),
+    ];
+    assert_eq!(item_changes(release_items(), new), vec!["create [ ] close the sprint"]);
+    let new = vec![item(0, "notify the team", false)];
+    assert_eq!(item_changes(release_items(), new), vec!["delete 1", "delete 2"]);
+}
+
+#[test]
+fn renamed_item_keeps_its_id_when_another_is_moved_and_removed() {
+    // "build artifacts" is removed, "notify the team" is moved up and renamed
+    let new = vec![
+        item(0, "notify the whole team", false),
+        item(0, "deploy to staging", false),
+    ];
+    assert_eq!(
+        item_changes(release_items(), new),
+        vec!["update 3 [ ] notify the whole team", "delete 1"]
+    );
+}
+
+#[test]
+fn duplicate_items_are_paired_once() {
+    let old = vec![item(1, "review", false), item(2, "review", true)];
+    let new = vec![item(0, "review", true)];
+    // equal texts are paired in order
+    assert_eq!(
+        item_changes(old.clone(), new),
+        vec!["update 1 [x] review", "delete 2"]
+    );
+    let new = vec![
+        item(0, "review", false),
+        item(0, "review", true),
+        item(0, "review", false),
+    ];
+    assert_eq!(item_changes(old, new), vec!["create [ ] review"]);
+}
+
+fn checklist_changes(old: &[Checklist], new: &[Checklist]) -> Vec<String> {
+    Checklist::diff(old, new)
+        .into_iter()
+        .map(|change| match change {
+            ChecklistChange::Rename { id, name } => format!("rename {} {}", id, name),
+            ChecklistChange::Create { name, items } => format!("create {} {}", name, items.len()),
+            ChecklistChange::Delete { id } => format!("delete {}", id),
+            other => panic!("unexpected change {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn diff_renames_creates_and_deletes_checklists() {
+    let old = vec![
+        checklist(1, "Release", release_items()),
+        checklist(2, "QA", vec![item(4, "smoke tests", false)]),
+    ];
+    let new = vec![checklist(0, "Release 2", release_items())];
+    assert_eq!(checklist_changes(&old, &new), vec!["rename 1 Release 2", "delete 2"]);
+    let new = vec![
+        checklist(0, "QA", vec![item(0, "smoke tests", false)]),
+        checklist(0, "Release", release_items()),
+        checklist(0, "Docs", vec![item(0, "update README", false)]),
+    ];
+    assert_eq!(checklist_changes(&old, &new), vec!["create Docs 1"]);
+}
@@ -0,0 +1,306 @@
+mod common;
+
+use common::{stderr, Cli, TOKEN};
+use serde_json::{json, Value};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+
+#[test]
+fn init_downloads_entities_cache() {
+    let cli = Cli::initialized();
+    for path in ["spaces", "spaces/1/users", "spaces/1/boards/10", "tags", "card-types"] {
+        assert_eq!(cli.mock.requests_to("GET", path).len(), 1, "GET {}", path);
+    }
+    assert!(cli.config_dir().join("entities.yaml").exists());
+
+    let entities = cli.stdout(&["init", "--show"]);
+    assert!(entities.contains("Development"));
+    assert!(entities.contains("alice"));
+}
+
+#[test]
+fn requests_are_authorized_with_token() {
+    let cli = Cli::new();
+    cli.stdout(&["cards", "get", "5"]);
+    let requests = cli.mock.requests_to("GET", "cards/5");
+    assert_eq!(requests[0].authorization, Some(format!("Bearer {}", TOKEN)));
+}
+
+#[test]
+fn cards_ls_renders_table() {
+    let cli = Cli::initialized();
+    let table = cli.stdout(&["cards", "ls"]);
+    assert!(table.contains("Fix login crash"));
+    assert!(table.contains("Dark mode"));
+    assert!(table.contains("In progress"));
+    assert!(table.find("Fix login crash") < table.find("Dark mode"));
+    assert_eq!(table.matches("Dark mode").count(), 1, "a short page is the last one");
+
+    // a few pages are requested ahead, their cards are dropped
+    let requests = cli.mock.requests_to("GET", "cards");
+    assert!(requests
+        .iter()
+        .any(|request| request.query == "condition=1&states=1%2C2&limit=100&offset=0"));
+}
+
+#[test]
+fn cards_ls_streams_csv_pages() {
+    let cli = Cli::initialized();
+    let csv = cli.stdout(&["cards", "ls", "--output", "csv"]);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 3, "{}", csv);
+    assert!(lines[1].contains("Fix login crash"));
+    assert!(lines[2].contains("Dark mode"));
+}
+
+#[test]
+fn cards_ls_writes_no_csv_when_first_page_fails() {
+    let cli = Cli::initialized();
+    let output = cli
+        .command(&["cards", "ls", "--output", "csv"])
+        .env("API_URL", format!("{}missing/", cli.mock.url()))
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(12));
+    assert!(output.stdout.is_empty(), "{}", String::from_utf8_lossy(&output.stdout));
+}
+
+#[test]
+fn cards_ls_encodes_filters() {
+    let cli = Cli::initialized();
+    cli.stdout(&[
+        "cards", "ls", "--query", "bug & crash", "--tag", "to do", "--exclude-column-ids", "3,4",
+        "--limit", "10",
+    ]);
+    let requests = cli.mock.requests_to("GET", "cards");
+    assert_eq!(
+        requests[0].query,
+        "condition=1&states=1%2C2&exclude_column_ids=3%2C4&query=bug+%26+crash&tag=to+do&limit=10"
+    );
+}
+
+#[test]
+fn cards_ls_resolves_column_and_lane_names() {
+    let cli = Cli::initialized();
+    cli.stdout(&[
+        "--board-id", "10", "cards", "ls", "--column", "in progress/rev", "--lane", "mob", "--limit", "5",
+    ]);
+    let requests = cli.mock.requests_to("GET", "cards");
+    assert_eq!(
+        requests[0].query,
+        "condition=1&states=1%2C2&board_id=10&column_id=102&lane_id=201&limit=5"
+    );
+}
+
+#[test]
+fn cards_ls_column_ids_work_without_cache() {
+    let cli = Cli::new();
+    cli.stdout(&["cards", "ls", "--column-id", "100", "--limit", "5"]);
+    let requests = cli.mock.requests_to("GET", "cards");
+    assert_eq!(requests[0].query, "condition=1&states=1%2C2&column_id=100&limit=5");
+
+    let output = cli.run(&["cards", "ls", "--column", "Queue"]);
+    assert_eq!(output.status.code(), Some(4));
+    assert!(stderr(&output).contains("column `Queue`"), "{}", stderr(&output));
+}
+
+#[test]
+fn cards_ls_rejects_invalid_id_list() {
+    let cli = Cli::new();
+    let output = cli.run(&["cards", "ls", "--tag-ids", "1,x"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("`x` in `1,x` is not an id"));
+    assert!(cli.mock.requests().is_empty());
+}
+
+#[test]
+fn cards_get_prints_json() {
+    let cli = Cli::new();
+    let card: Value = serde_json::from_str(&cli.stdout(&["cards", "get", "5", "-o", "json"])).unwrap();
+    assert_eq!(card["id"], 5);
+    assert_eq!(card["title"], "Fix login crash");
+    assert_eq!(card["column"], json!({ "id": 100, "title": "Queue" }));
+    assert_eq!(card["tags"], json!(["bug"]));
+    assert_eq!(card["checklists"][0]["items"][0]["text"], "Update changelog");
+}
+
+#[test]
+fn cards_edit_sends_changed_fields_only() {
+    let cli = Cli::initialized();
+    cli.stdout(&["cards", "edit", "5", "--title", "Fix logout crash", "--type", "B", "--asap"]);
+    assert_eq!(
+        cli.mock.body_of("PATCH", "cards/5"),
+        json!({ "title": "Fix logout crash", "type_id": 8, "asap": true })
+    );
+}
+
+#[test]
+fn cards_edit_check_updated_fails_without_timestamp() {
+    let cli = Cli::initialized();
+    let output = cli.run(&["cards", "edit", "6", "--title", "Dark theme", "--check-updated"]);
+    assert_eq!(output.status.code(), Some(6));
+    assert!(stderr(&output).contains("no `updated` timestamp"), "{}", stderr(&output));
+    assert!(cli.mock.requests_to("PATCH", "cards/6").is_empty());
+}
+
+#[test]
+fn cards_mv_sends_column_and_lane() {
+    let cli = Cli::initialized();
+    cli.stdout(&["cards", "mv", "5", "--column", "In progress/Review", "--lane", "mob"]);
+    assert_eq!(
+        cli.mock.body_of("PATCH", "cards/5"),
+        json!({ "column_id": 102, "lane_id": 201 })
+    );
+}
+
+#[test]
+fn cards_mv_to_unknown_column_fails_before_patch() {
+    let cli = Cli::initialized();
+    let output = cli.run(&["cards", "mv", "5", "--column", "Archive"]);
+    assert_eq!(output.status.code(), Some(5));
+    assert!(cli.mock.requests_to("PATCH", "cards/5").is_empty());
+}
+
+#[test]
+fn comments_get_renders_table() {
+    let cli = Cli::new();
+    let table = cli.stdout(&["comments", "get", "5"]);
+    assert!(table.contains("Looks good to me"));
+    assert!(table.contains("bob"));
+}
+
+#[test]
+fn comments_new_posts_text() {
+    let cli = Cli::new();
+    let table = cli.stdout(&["comments", "new", "5", "@alice review, please"]);
+    assert_eq!(
+        cli.mock.body_of("POST", "cards/5/comments"),
+        json!({ "text": "@alice review, please" })
+    );
+    assert!(table.contains("@alice review, please"));
+}
+
+#[test]
+fn links_new_posts_url_and_description() {
+    let cli = Cli::new();
+    cli.stdout(&[
+        "links", "new", "5", "--link", "https://dev.example.com", "--description", "dev-stand",
+    ]);
+    assert_eq!(
+        cli.mock.body_of("POST", "cards/5/external-links"),
+        json!({ "url": "https://dev.example.com", "description": "dev-stand" })
+    );
+}
+
+#[test]
+fn not_found_exits_with_code() {
+    let cli = Cli::new();
+    let output = cli.run(&["cards", "get", "404"]);
+    assert_eq!(output.status.code(), Some(12));
+    assert!(stderr(&output).contains("Error:"));
+}
+
+#[test]
+fn missing_cache_exits_with_code() {
+    let cli = Cli::new();
+    let output = cli.run(&["cards", "mv", "5", "--column", "Done"]);
+    assert_eq!(output.status.code(), Some(4));
+    assert!(stderr(&output).contains("kaiten-cli init"));
+}
+
+#[test]
+fn missing_credentials_hint_login() {
+    let cli = Cli::new();
+    let output = cli.without_env(&["cards", "get", "5"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("kaiten-cli login"), "{}", stderr(&output));
+}
+
+#[test]
+fn token_with_line_break_is_a_credentials_error() {
+    let cli = Cli::new();
+    let output = cli.command(&["cards", "get", "5"]).env("KT", "abc\ndef").output().unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("line break"), "{}", stderr(&output));
+    assert!(stderr(&output).contains("kaiten-cli login"), "{}", stderr(&output));
+    assert!(cli.mock.requests().is_empty());
+}
+
+#[test]
+fn invalid_variable_does_not_hint_credentials() {
+    let cli = Cli::new();
+    let output = cli.command(&["cards", "get", "5"]).env("KAITEN_RETRIES", "many").output().unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("KAITEN_RETRIES"));
+    assert!(!stderr(&output).contains("kaiten-cli login"), "{}", stderr(&output));
+}
+
+/// `$EDITOR` which replaces the file with `text`
+fn editor_writing(cli: &Cli, text: &str) -> PathBuf {
+    let template = cli.home().join("template.md");
+    fs::write(&template, text).unwrap();
+    let editor = cli.home().join("editor.sh");
+    fs::write(&editor, format!("#!/bin/sh\ncp '{}' \"$1\"\n", template.display())).unwrap();
+    fs::set_permissions(&editor, fs::Permissions::from_mode(0o755)).unwrap();
+    editor
+}
+
+#[test]
+fn cards_new_posts_card() {
+    let cli = Cli::initialized();
+    let editor = editor_writing(&cli, "---\ntitle: New card\nboard: Development\n---\nText\n");
+    let output = cli.command(&["cards", "new"]).env("EDITOR", &editor).output().unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Created card 7"));
+    assert_eq!(
+        cli.mock.body_of("POST", "cards"),
+        json!({ "title": "New card", "board_id": 10, "column_id": 100, "lane_id": 200, "description": "Text" })
+    );
+}
+
+#[test]
+fn cards_new_reports_card_id_when_tags_fail() {
+    let cli = Cli::initialized();
+    let editor = editor_writing(&cli, "---\ntitle: New card\nboard: Development\ntags: [bug]\n---\n");
+    let output = cli.command(&["cards", "new"]).env("EDITOR", &editor).output().unwrap();
+    assert_eq!(output.status.code(), Some(12));
+    assert!(stderr(&output).contains("Created card 7"), "{}", stderr(&output));
+    assert_eq!(cli.mock.requests_to("POST", "cards").len(), 1);
+}
+
+#[test]
+fn blockers_ls_lists_active_blockers_of_the_board() {
+    let cli = Cli::new();
+    let table = cli.stdout(&["--board-id", "11", "blockers", "ls", "--board"]);
+    assert!(table.contains("waiting for card 5 review"), "{}", table);
+    assert!(table.contains("waiting for card 6 review"), "{}", table);
+    assert!(!table.contains("released one"));
+    assert!(table.find("card 5") < table.find("card 6"), "cards keep their order");
+    assert_eq!(cli.mock.requests_to("GET", "cards/5/blockers").len(), 1);
+    assert_eq!(cli.mock.requests_to("GET", "cards/6/blockers").len(), 1);
+}
+
+#[test]
+fn blockers_ls_reads_every_page() {
+    let cli = Cli::new();
+    let table = cli.stdout(&["--board-id", "12", "blockers", "ls", "--board"]);
+    assert!(table.contains("waiting for card 6 review"), "{}", table);
+    assert!(!table.contains("card 5"), "{}", table);
+    let pages: Vec<String> = cli
+        .mock
+        .requests_to("GET", "cards")
+        .into_iter()
+        .map(|request| request.query)
+        .collect();
+    assert!(pages.iter().any(|query| query.ends_with("limit=100&offset=100")), "{:?}", pages);
+}
+
+#[test]
+fn blockers_ls_requires_board() {
+    let cli = Cli::new();
+    let output = cli.run(&["blockers", "ls", "--board"]);
+    assert_eq!(output.status.code(), Some(5));
+    assert!(stderr(&output).contains("Board is not set"));
+    assert!(cli.mock.requests().is_empty());
+}
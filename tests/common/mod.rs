@@ -0,0 +1,342 @@
+//! Stand-in for the Kaiten API and a runner of the CLI binary against it.
+//! Responses come from `tests/fixtures`, every request is recorded for assertions
+#![allow(dead_code)]
+
+pub mod snapshot;
+
+use kaiten_cli::models::{Board, Info, Space};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use tempfile::TempDir;
+use tiny_http::{Header, Response, Server};
+
+pub const TOKEN: &str = "test-token";
+/// Token which the mock answers with `401`
+pub const BAD_TOKEN: &str = "bad-token";
+
+/// Request received by the mock
+#[derive(Debug, Clone)]
+pub struct Recorded {
+    pub method: String,
+    pub path: String,
+    /// Raw url query, empty when there is none
+    pub query: String,
+    pub authorization: Option<String>,
+    pub body: Option<Value>,
+}
+
+pub struct MockKaiten {
+    url: String,
+    server: Arc<Server>,
+    requests: Arc<Mutex<Vec<Recorded>>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MockKaiten {
+    pub fn start() -> Self {
+        let server = Arc::new(Server::http("127.0.0.1:0").expect("mock server failed to start"));
+        let port = server.server_addr().to_ip().expect("mock server has no ip address").port();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handle = {
+            let server = Arc::clone(&server);
+            let requests = Arc::clone(&requests);
+            thread::spawn(move || {
+                for mut request in server.incoming_requests() {
+                    let method = request.method().to_string();
+                    let (path, query) = match request.url().split_once('?') {
+                        Some((path, query)) => (path.to_string(), query.to_string()),
+                        None => (request.url().to_string(), String::new()),
+                    };
+                    let authorization = request
+                        .headers()
+                        .iter()
+                        .find(|header| header.field.equiv("Authorization"))
+                        .map(|header| header.value.to_string());
+                    let mut content = String::new();
+                    let _ = request.as_reader().read_to_string(&mut content);
+                    let body = serde_json::from_str(&content).ok();
+                    let (status, response) = if authorization == Some(format!("Bearer {}", BAD_TOKEN)) {
+                        (401, json!({ "message": "Unauthorized" }))
+                    } else {
+                        respond(&method, &path, &query, body.as_ref())
+                    };
+                    requests.lock().unwrap().push(Recorded {
+                        method,
+                        path,
+                        query,
+                        authorization,
+                        body,
+                    });
+                    let response = Response::from_string(response.to_string())
+                        .with_status_code(status)
+                        .with_header(
+                            Header::from_bytes("Content-Type", "application/json").unwrap(),
+                        );
+                    let _ = request.respond(response);
+                }
+            })
+        };
+        Self {
+            url: format!("http://127.0.0.1:{}/api/latest/", port),
+            server,
+            requests,
+            handle: Some(handle),
+        }
+    }
+
+    /// Base url for `API_URL`
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn requests(&self) -> Vec<Recorded> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Requests with `method` to `path`, relative to the api url like `cards/5`
+    pub fn requests_to(&self, method: &str, path: &str) -> Vec<Recorded> {
+        let path = format!("/api/latest/{}", path);
+        self.requests()
+            .into_iter()
+            .filter(|request| request.method == method && request.path == path)
+            .collect()
+    }
+
+    /// Body of the only `method` request to `path`
+    pub fn body_of(&self, method: &str, path: &str) -> Value {
+        let requests = self.requests_to(method, path);
+        assert_eq!(requests.len(), 1, "expected one {} {}, got {:#?}", method, path, self.requests());
+        requests[0].body.clone().expect("request has no json body")
+    }
+}
+
+impl Drop for MockKaiten {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+pub fn fixture(name: &str) -> Value {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name);
+    let content = fs::read_to_string(&path).unwrap_or_else(|err| panic!("{:?}: {}", path, err));
+    serde_json::from_str(&content).unwrap_or_else(|err| panic!("{:?}: {}", path, err))
+}
+
+/// Entities cache of the fixtures, the same as `kaiten-cli init` downloads from the mock
+pub fn fixture_info() -> Info {
+    fn from<T: serde::de::DeserializeOwned>(name: &str) -> T {
+        serde_json::from_value(fixture(name)).unwrap()
+    }
+    let mut spaces: Vec<Space> = from("spaces.json");
+    for space in spaces.iter_mut() {
+        space.set_users(from("users.json"));
+    }
+    let board: Board = from("board_10.json");
+    Info::from(
+        spaces.into_iter().map(|space| (space.get_id(), space)).collect(),
+        HashMap::from([(board.get_id(), board)]),
+        from("tags.json"),
+        from("card_types.json"),
+    )
+}
+
+/// Routes of the Kaiten API used by the CLI, unknown ones answer `404`
+fn respond(method: &str, path: &str, query: &str, body: Option<&Value>) -> (u16, Value) {
+    let path = path.trim_start_matches("/api/latest");
+    let text = |field: &str| body.and_then(|body| body.get(field)).cloned().unwrap_or(Value::Null);
+    match (method, path) {
+        ("GET", "/users/current") => (200, fixture("users.json")[0].clone()),
+        ("GET", "/spaces") => (200, fixture("spaces.json")),
+        ("GET", "/spaces/1/users") => (200, fixture("users.json")),
+        ("GET", "/spaces/1/boards/10") => (200, fixture("board_10.json")),
+        ("GET", "/tags") => (200, fixture("tags.json")),
+        ("GET", "/card-types") => (200, fixture("card_types.json")),
+        // every card on board 11 is blocked
+        ("GET", "/cards") if query.contains("board_id=11") => {
+            let mut cards = fixture("cards.json");
+            for card in cards.as_array_mut().unwrap() {
+                card["blocked"] = json!(true);
+            }
+            (200, cards)
+        }
+        // board 12 has a full page of cards and card 6 blocked on the second one
+        ("GET", "/cards") if query.contains("board_id=12") => {
+            let cards = fixture("cards.json");
+            let page = if query.contains("offset=0") {
+                (1000..1100)
+                    .map(|id| {
+                        let mut card = cards[0].clone();
+                        card["id"] = json!(id);
+                        card
+                    })
+                    .collect()
+            } else if query.contains("offset=100") {
+                let mut card = cards[1].clone();
+                card["blocked"] = json!(true);
+                vec![card]
+            } else {
+                Vec::new()
+            };
+            (200, Value::Array(page))
+        }
+        ("GET", "/cards") => (200, fixture("cards.json")),
+        ("GET", "/cards/5/blockers") | ("GET", "/cards/6/blockers") => {
+            let card_id = path.split('/').nth(2).unwrap();
+            (
+                200,
+                json!([
+                    {
+                        "id": 1,
+                        "reason": format!("waiting for card {} review", card_id),
+                        "blocker_card_id": null,
+                        "blocker": null,
+                        "released": false,
+                        "created": "2024-01-01T10:00:00Z",
+                        "updated": "2024-01-01T10:00:00Z"
+                    },
+                    {
+                        "id": 2,
+                        "reason": "released one",
+                        "blocker_card_id": 9,
+                        "blocker": null,
+                        "released": true,
+                        "created": "2024-01-01T10:00:00Z",
+                        "updated": "2024-01-01T10:00:00Z"
+                    }
+                ]),
+            )
+        }
+        // the new card has no routes for its tags and members
+        ("POST", "/cards") => (200, json!({ "id": 7, "title": text("title") })),
+        ("GET", "/cards/5") | ("PATCH", "/cards/5") => (200, fixture("card_5.json")),
+        // card 6 comes without `updated`
+        ("GET", "/cards/6") => {
+            let mut card = fixture("cards.json")[1].clone();
+            card.as_object_mut().unwrap().remove("updated");
+            (200, card)
+        }
+        ("GET", "/cards/5/comments") => (200, fixture("comments.json")),
+        ("POST", "/cards/5/comments") => (
+            200,
+            json!({
+                "id": 32,
+                "text": text("text"),
+                "created": "2024-01-04T12:00:00Z",
+                "edited": false,
+                "author": { "id": 5, "username": "alice" }
+            }),
+        ),
+        ("GET", "/cards/5/external-links") => (200, fixture("links.json")),
+        ("POST", "/cards/5/external-links") => (
+            200,
+            json!({ "id": 42, "url": text("url"), "description": text("description") }),
+        ),
+        _ => (404, json!({ "message": format!("{} {} not found", method, path) })),
+    }
+}
+
+/// The CLI with its own `HOME`, so the config and the entities cache of the user are not touched
+pub struct Cli {
+    pub mock: MockKaiten,
+    home: TempDir,
+}
+
+impl Cli {
+    pub fn new() -> Self {
+        Self {
+            mock: MockKaiten::start(),
+            home: TempDir::new().expect("failed to create temp HOME"),
+        }
+    }
+
+    /// With the entities cache downloaded from the mock by `kaiten-cli init`
+    pub fn initialized() -> Self {
+        let cli = Self::new();
+        let output = cli.run(&["init"]);
+        assert!(output.status.success(), "init failed: {}", stderr(&output));
+        cli
+    }
+
+    /// Temporary `HOME`, also the working directory of the commands
+    pub fn home(&self) -> &Path {
+        self.home.path()
+    }
+
+    pub fn config_dir(&self) -> PathBuf {
+        self.home.path().join(".config").join("kaiten-cli")
+    }
+
+    pub fn command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_kaiten-cli"));
+        command
+            .args(args)
+            // no `.kaiten.yaml` of the checkout is found from here
+            .current_dir(self.home.path())
+            .env("HOME", self.home.path())
+            .env("API_URL", self.mock.url())
+            .env("KT", TOKEN)
+            .env("KAITEN_RATE_LIMIT", "0")
+            .env("KAITEN_RETRIES", "0")
+            .env("NO_COLOR", "1")
+            .env_remove("SPACE_ID")
+            .env_remove("BOARD_ID")
+            .env_remove("KAITEN_SPACE_ID")
+            .env_remove("KAITEN_BOARD_ID")
+            .env_remove("KAITEN_EXCLUDE_BOARD_IDS")
+            .env_remove("KAITEN_EXCLUDE_LANE_IDS")
+            .env_remove("KAITEN_EXCLUDE_COLUMN_IDS")
+            .env_remove("KAITEN_PROFILE")
+            .env_remove("EDITOR")
+            .env_remove("VISUAL");
+        command
+    }
+
+    /// Without `API_URL` and `KT`, like after `kaiten-cli login`
+    pub fn without_env(&self, args: &[&str]) -> Command {
+        let mut command = self.command(args);
+        command.env_remove("API_URL").env_remove("KT");
+        command
+    }
+
+    /// Run with `input` written to stdin
+    pub fn run_with_stdin(&self, mut command: Command, input: &str) -> Output {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("failed to run kaiten-cli");
+        child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+        child.wait_with_output().expect("failed to run kaiten-cli")
+    }
+
+    pub fn run(&self, args: &[&str]) -> Output {
+        self.command(args).output().expect("failed to run kaiten-cli")
+    }
+
+    /// Stdout of a command which must succeed
+    pub fn stdout(&self, args: &[&str]) -> String {
+        let output = self.run(args);
+        assert!(
+            output.status.success(),
+            "kaiten-cli {} failed with {:?}: {}",
+            args.join(" "),
+            output.status.code(),
+            stderr(&output)
+        );
+        String::from_utf8(output.stdout).expect("stdout is not utf-8")
+    }
+}
+
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
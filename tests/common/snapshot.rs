@@ -0,0 +1,28 @@
+//! Golden files in `tests/snapshots`.
+//! Run `UPDATE_SNAPSHOTS=1 cargo test` to write them after an intended change of the output
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+pub fn assert_snapshot(name: &str, actual: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{}.txt", name));
+    let actual = format!("{}\n", actual.trim_end());
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, &actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|err| {
+        panic!("{:?}: {}, run with UPDATE_SNAPSHOTS=1 to create it", path, err)
+    });
+    if expected != actual {
+        panic!(
+            "snapshot {:?} differs, run with UPDATE_SNAPSHOTS=1 if the change is intended\n\
+             --- expected\n{}\n+++ actual\n{}",
+            path, expected, actual
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use chrono::NaiveDate;
+use kaiten_cli::models::due_date::parse_due_date;
+
+/// A Wednesday
+fn today() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 1, 10).unwrap()
+}
+
+fn parse(input: &str) -> Result<Option<String>, String> {
+    parse_due_date(input, today())
+}
+
+fn date(input: &str) -> String {
+    parse(input).unwrap().unwrap()
+}
+
+#[test]
+fn iso_dates_and_datetimes() {
+    assert_eq!(date("2024-02-29"), "2024-02-29");
+    assert_eq!(date(" 2024-03-01 "), "2024-03-01");
+    assert_eq!(date("2024-03-01T10:00:00+03:00"), "2024-03-01T10:00:00+03:00");
+    assert!(parse("2024-02-30").is_err());
+}
+
+#[test]
+fn today_and_tomorrow() {
+    assert_eq!(date("today"), "2024-01-10");
+    assert_eq!(date("Tomorrow"), "2024-01-11");
+}
+
+#[test]
+fn weekdays_are_the_next_one_or_today() {
+    assert_eq!(date("wednesday"), "2024-01-10");
+    assert_eq!(date("thu"), "2024-01-11");
+    assert_eq!(date("Friday"), "2024-01-12");
+    assert_eq!(date("mon"), "2024-01-15");
+    assert_eq!(date("tue"), "2024-01-16");
+}
+
+#[test]
+fn offsets() {
+    assert_eq!(date("+3d"), "2024-01-13");
+    assert_eq!(date("-1d"), "2024-01-09");
+    assert_eq!(date("+2w"), "2024-01-24");
+    assert_eq!(date("-1w"), "2024-01-03");
+    assert_eq!(date("+0d"), "2024-01-10");
+}
+
+#[test]
+fn clear_removes_due_date() {
+    assert_eq!(parse("clear").unwrap(), None);
+    assert_eq!(parse("none").unwrap(), None);
+}
+
+#[test]
+fn invalid_input_is_an_error() {
+    for input in ["", "soon", "+d", "+3", "3d", "+3x", "+-3d", "+3.5d"] {
+        let err = parse(input).unwrap_err();
+        assert!(err.starts_with("Invalid due date"), "{}: {}", input, err);
+    }
+}
+
+#[test]
+fn overflow_is_out_of_range() {
+    for input in ["+999999999d", "-999999999d", "+99999999999999999d", "+99999999999999999999d", "+9999999999999w"] {
+        let err = parse(input).unwrap_err();
+        assert!(err.contains("out of range"), "{}: {}", input, err);
+    }
+    assert!(parse_due_date("tomorrow", NaiveDate::MAX).unwrap_err().contains("out of range"));
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use common::{stderr, Cli, MockKaiten};
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// `repo/.kaiten.yaml` with `content`, returns `repo/src` to run the commands from
+fn repo(cli: &Cli, content: &str) -> PathBuf {
+    let repo = cli.home().join("repo");
+    fs::create_dir_all(repo.join("src")).unwrap();
+    fs::write(repo.join(".kaiten.yaml"), content).unwrap();
+    repo.join("src")
+}
+
+fn stdout(mut command: Command) -> String {
+    let output = command.output().unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// Query of the only `GET /cards` of `cards ls --limit 5`
+fn ls_query(mock: &MockKaiten, command: Command) -> String {
+    let before = mock.requests_to("GET", "cards").len();
+    stdout(command);
+    mock.requests_to("GET", "cards")[before].query.clone()
+}
+
+/// Row of `config show --origin` for `key`
+fn origin_of(show: &str, key: &str) -> String {
+    show.lines()
+        .find(|line| line.contains(&format!(" {} ", key)))
+        .unwrap_or_else(|| panic!("no {} in {}", key, show))
+        .to_string()
+}
+
+#[test]
+fn repository_config_is_found_from_subdirectory() {
+    let cli = Cli::new();
+    cli.stdout(&["config", "set", "--space-id", "1", "--board-id", "3"]);
+    let dir = repo(&cli, "board_id: 10\nexclude_lane_ids: 201,202\n");
+
+    let mut command = cli.command(&["cards", "ls", "--limit", "5"]);
+    command.current_dir(&dir);
+    assert_eq!(
+        ls_query(&cli.mock, command),
+        "condition=1&states=1%2C2&space_id=1&board_id=10&exclude_lane_ids=201%2C202&limit=5"
+    );
+
+    let mut command = cli.command(&["config", "show", "--origin"]);
+    command.current_dir(&dir);
+    let show = stdout(command);
+    assert!(origin_of(&show, "space_id").contains("config.yaml"), "{}", show);
+    assert!(origin_of(&show, "board_id").contains(".kaiten.yaml"), "{}", show);
+    assert!(origin_of(&show, "exclude_board_ids").contains("default"), "{}", show);
+    // outside of the repository only the global config is used
+    assert!(origin_of(&cli.stdout(&["config", "show", "--origin"]), "board_id").contains("config.yaml"));
+}
+
+#[test]
+fn env_overrides_files_and_flags_override_env() {
+    let cli = Cli::new();
+    cli.stdout(&["config", "set", "--board-id", "3"]);
+    let dir = repo(&cli, "board_id: 10\n");
+
+    let mut command = cli.command(&["config", "show", "--origin"]);
+    command.current_dir(&dir).env("KAITEN_BOARD_ID", "11");
+    let show = stdout(command);
+    assert!(origin_of(&show, "board_id").contains("11"), "{}", show);
+    assert!(origin_of(&show, "board_id").contains("env KAITEN_BOARD_ID"), "{}", show);
+
+    let mut command = cli.command(&["config", "show", "--origin", "--board-id", "12"]);
+    command.current_dir(&dir).env("KAITEN_BOARD_ID", "11");
+    let show = stdout(command);
+    assert!(origin_of(&show, "board_id").contains("12"), "{}", show);
+    assert!(origin_of(&show, "board_id").contains("flag --board-id"), "{}", show);
+}
+
+#[test]
+fn board_id_variable_sets_the_board() {
+    let cli = Cli::new();
+    let mut command = cli.command(&["cards", "ls", "--limit", "5"]);
+    command.env("SPACE_ID", "1").env("BOARD_ID", "7");
+    assert_eq!(
+        ls_query(&cli.mock, command),
+        "condition=1&states=1%2C2&space_id=1&board_id=7&limit=5"
+    );
+}
+
+#[test]
+fn invalid_variable_is_a_config_error() {
+    let cli = Cli::new();
+    let output = cli.command(&["cards", "ls"]).env("KAITEN_BOARD_ID", "ten").output().unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("KAITEN_BOARD_ID"));
+}
+
+#[test]
+fn repository_config_selects_profile() {
+    let cli = Cli::new();
+    let client = MockKaiten::start();
+    cli.stdout(&["config", "profiles", "add", "client", "--api-url", client.url(), "--board-id", "20"]);
+    let dir = repo(&cli, "profile: client\nexclude_column_ids: 5\n");
+
+    let mut command = cli.command(&["cards", "ls", "--limit", "5"]);
+    command.current_dir(&dir);
+    assert_eq!(
+        ls_query(&client, command),
+        "condition=1&states=1%2C2&board_id=20&exclude_column_ids=5&limit=5"
+    );
+    assert!(cli.mock.requests().is_empty());
+
+    let mut command = cli.command(&["config", "show", "--origin"]);
+    command.current_dir(&dir);
+    let show = stdout(command);
+    assert!(origin_of(&show, "profile").contains(".kaiten.yaml"), "{}", show);
+    assert!(origin_of(&show, "board_id").contains("profile client"), "{}", show);
+}
+
+#[test]
+fn ignore_config_keeps_flags() {
+    let cli = Cli::new();
+    cli.stdout(&["config", "set", "--space-id", "1"]);
+    let dir = repo(&cli, "board_id: 10\n");
+
+    let mut command = cli.command(&["cards", "ls", "--limit", "5", "--ignore-config", "--board-id", "12"]);
+    command.current_dir(&dir);
+    assert_eq!(
+        ls_query(&cli.mock, command),
+        "condition=1&states=1%2C2&board_id=12&limit=5"
+    );
+}
+
+#[test]
+fn invalid_repository_config_names_file_and_line() {
+    let cli = Cli::new();
+    let dir = repo(&cli, "space_id: 1\nexclude_lane_ids: 1,,x\n");
+    let file = cli.home().join("repo").join(".kaiten.yaml");
+
+    let output = cli.command(&["cards", "ls"]).current_dir(&dir).output().unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    let stderr = stderr(&output);
+    assert!(stderr.contains(&format!("{}:2:", file.display())), "{}", stderr);
+    assert!(stderr.contains(&format!("Hint: Fix or remove {}", file.display())), "{}", stderr);
+    assert!(!stderr.contains("API_URL"), "{}", stderr);
+    assert!(cli.mock.requests().is_empty());
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use common::{stderr, Cli, MockKaiten, TOKEN};
+use std::fs;
+
+const CLIENT_TOKEN: &str = "client-token";
+
+/// The default instance is `cli.mock`, the profile `client` points to the returned one
+fn with_client_profile(cli: &Cli) -> MockKaiten {
+    let client = MockKaiten::start();
+    cli.stdout(&[
+        "config", "profiles", "add", "client", "--api-url", client.url(), "--token-env",
+        "CLIENT_KT", "--board-id", "10", "--exclude-lane-ids", "201",
+    ]);
+    client
+}
+
+fn run(cli: &Cli, args: &[&str]) -> String {
+    let output = cli.command(args).env("CLIENT_KT", CLIENT_TOKEN).output().unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn profile_flag_selects_instance_and_token() {
+    let cli = Cli::new();
+    let client = with_client_profile(&cli);
+
+    run(&cli, &["--profile", "client", "cards", "get", "5"]);
+    let requests = client.requests_to("GET", "cards/5");
+    assert_eq!(requests[0].authorization, Some(format!("Bearer {}", CLIENT_TOKEN)));
+    assert!(cli.mock.requests().is_empty());
+
+    run(&cli, &["cards", "get", "5"]);
+    let requests = cli.mock.requests_to("GET", "cards/5");
+    assert_eq!(requests[0].authorization, Some(format!("Bearer {}", TOKEN)));
+}
+
+#[test]
+fn profile_env_and_current_profile() {
+    let cli = Cli::new();
+    let client = with_client_profile(&cli);
+
+    let output = cli
+        .command(&["cards", "ls", "--limit", "5"])
+        .env("KAITEN_PROFILE", "client")
+        .env("CLIENT_KT", CLIENT_TOKEN)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+    let requests = client.requests_to("GET", "cards");
+    assert_eq!(
+        requests[0].query,
+        "condition=1&states=1%2C2&board_id=10&exclude_lane_ids=201&limit=5"
+    );
+
+    run(&cli, &["config", "profiles", "use", "client"]);
+    assert!(run(&cli, &["config", "profiles", "ls"]).contains("yes"));
+    run(&cli, &["cards", "get", "5"]);
+    assert_eq!(client.requests_to("GET", "cards/5").len(), 1);
+    assert!(cli.mock.requests().is_empty());
+
+    run(&cli, &["config", "profiles", "use"]);
+    run(&cli, &["cards", "get", "5"]);
+    assert_eq!(cli.mock.requests_to("GET", "cards/5").len(), 1);
+}
+
+#[test]
+fn profile_has_own_entities_cache() {
+    let cli = Cli::new();
+    let _client = with_client_profile(&cli);
+
+    run(&cli, &["--profile", "client", "init"]);
+    assert!(cli.config_dir().join("entities-client.yaml").exists());
+    assert!(!cli.config_dir().join("entities.yaml").exists());
+
+    let output = cli.run(&["users"]);
+    assert_eq!(output.status.code(), Some(4));
+    assert!(run(&cli, &["--profile", "client", "users"]).contains("alice"));
+
+    let logins = [
+        (vec!["--profile", "client", "login", "--with-token"], CLIENT_TOKEN),
+        (vec!["login", "--domain", cli.mock.url(), "--with-token"], TOKEN),
+    ];
+    for (args, token) in logins {
+        let output = cli.run_with_stdin(cli.without_env(&args), &format!("{}\n", token));
+        assert!(output.status.success(), "{}", stderr(&output));
+    }
+    let credentials = cli.config_dir().join("credentials.yaml");
+    assert!(fs::read_to_string(&credentials).unwrap().contains(CLIENT_TOKEN));
+
+    run(&cli, &["config", "profiles", "rm", "client"]);
+    assert!(!cli.config_dir().join("entities-client.yaml").exists());
+    let saved = fs::read_to_string(&credentials).unwrap();
+    assert!(!saved.contains("client"), "{}", saved);
+    assert!(saved.contains(TOKEN), "default login is kept: {}", saved);
+    let output = cli.run(&["--profile", "client", "users"]);
+    assert_eq!(output.status.code(), Some(3));
+}
+
+#[test]
+fn config_set_changes_profile_in_use() {
+    let cli = Cli::new();
+    let _client = with_client_profile(&cli);
+
+    run(&cli, &["--profile", "client", "config", "set", "--board-id", "11"]);
+    let config = run(&cli, &["config", "show"]);
+    assert!(config.contains("board_id: 11"), "{}", config);
+    assert!(config.contains("board_id: ~"), "top-level board is not changed: {}", config);
+}
+
+#[test]
+fn invalid_profile_name_is_rejected() {
+    let cli = Cli::new();
+    let output = cli.run(&["config", "profiles", "add", "../x", "--api-url", "http://x"]);
+    assert_eq!(output.status.code(), Some(2));
+    // `default` is the key of the login without a profile in credentials.yaml
+    let output = cli.run(&["config", "profiles", "add", "default", "--api-url", "http://x"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("`default` is reserved"), "{}", stderr(&output));
+}
+
+#[test]
+fn missing_selected_profile_can_be_added() {
+    let cli = Cli::new();
+    let with_new = |args: &[&str]| cli.command(args).env("KAITEN_PROFILE", "new").output().unwrap();
+
+    let output = with_new(&["cards", "get", "5"]);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("kaiten-cli config profiles add new"));
+
+    let output = with_new(&["config", "profiles", "ls"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let output = with_new(&["config", "profiles", "add", "new", "--api-url", cli.mock.url()]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let output = with_new(&["cards", "get", "5"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+}
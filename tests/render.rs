@@ -0,0 +1,132 @@
+//! Snapshots of the tables without colors, `tests/render_colors.rs` covers the color codes
+mod common;
+
+use chrono::{DateTime, FixedOffset};
+use common::fixture;
+use common::snapshot::assert_snapshot;
+use kaiten_cli::models::{Card, Column};
+use serde_json::{json, Value};
+use tabled::{settings::Style, Table};
+
+/// 2024-01-10 15:00 in Moscow, a week after the cards in the fixtures were moved
+fn now() -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339("2024-01-10T15:00:00+03:00").unwrap()
+}
+
+fn no_colors() {
+    colored::control::set_override(false);
+}
+
+fn card(changes: Value) -> Card {
+    let mut card = fixture("card_5.json");
+    card.as_object_mut()
+        .unwrap()
+        .extend(changes.as_object().unwrap().clone());
+    serde_json::from_value(card).unwrap()
+}
+
+fn related_card(id: u32, title: &str) -> Value {
+    json!({
+        "id": id,
+        "title": title,
+        "board_id": 10,
+        "column_id": 100,
+        "lane_id": 200,
+        "type": { "id": 7, "name": "Task", "letter": "T", "archived": false },
+        "condition": 1,
+        "state": 2
+    })
+}
+
+fn blocker(id: u32, reason: &str, card: Option<Value>) -> Value {
+    json!({
+        "id": id,
+        "reason": reason,
+        "blocker": { "id": 6, "username": "bob" },
+        "released": false,
+        "created": "2024-01-08T09:00:00Z",
+        "updated": "2024-01-08T09:00:00Z",
+        "card": card
+    })
+}
+
+fn columns() -> Vec<Column> {
+    serde_json::from_value(fixture("board_10.json")["columns"].clone()).unwrap()
+}
+
+#[test]
+fn card_details() {
+    no_colors();
+    assert_snapshot("card_details", &card(json!({})).to_table_string_at(now()));
+}
+
+#[test]
+fn card_details_with_due_date_and_blockers() {
+    no_colors();
+    let card = card(json!({
+        "due_date": "2024-01-09T00:00:00Z",
+        "blocked": true,
+        "blockers": [blocker(1, "Waiting for the API", Some(related_card(9, "Release API v2")))],
+        "blocking_blockers": [blocker(2, "Needs the login fix", Some(related_card(12, "Mobile login")))]
+    }));
+    assert_snapshot("card_details_blocked", &card.to_table_string_at(now()));
+}
+
+#[test]
+fn cards_ls_table() {
+    no_colors();
+    let cards: Vec<Card> = serde_json::from_value(fixture("cards.json")).unwrap();
+    assert_snapshot("cards_ls", &Card::list_to_table_string(&cards, false, now()));
+}
+
+#[test]
+fn cards_ls_table_with_due_dates() {
+    no_colors();
+    let cards = vec![
+        card(json!({ "due_date": "2024-01-09T00:00:00Z" })),
+        card(json!({
+            "id": 6,
+            "title": "Dark mode with a title which is long enough to be wrapped in the table column",
+            "due_date": "2024-01-12",
+            "last_moved_at": "2024-01-10T10:30:00Z"
+        })),
+        card(json!({ "id": 7, "title": "Done card", "state": 3, "due_date": "2024-01-01" })),
+    ];
+    assert_snapshot("cards_ls_due", &Card::list_to_table_string(&cards, true, now()));
+}
+
+#[test]
+fn subcolumns() {
+    no_colors();
+    let mut subcolumns = columns()[1].subcolumns.clone().unwrap();
+    subcolumns.insert(
+        0,
+        serde_json::from_value(json!({
+            "id": 104, "title": "Testing", "board_id": 10, "sort_order": 2.0,
+            "column_id": 101, "subcolumns": null
+        }))
+        .unwrap(),
+    );
+    let display = Column::dispay_subcolumns(&Some(subcolumns));
+    let table = Table::new(columns()).with(Style::modern()).to_string();
+    assert_snapshot("subcolumns", &format!("{}\n\n{}", display, table));
+}
+
+#[test]
+fn move_diff() {
+    no_colors();
+    let now = now().to_utc();
+    let lines: Vec<String> = [
+        "2024-01-10T12:00:00Z",
+        "2024-01-10T07:30:00Z",
+        "2024-01-09T12:00:00Z",
+        "2024-01-09T11:00:00Z",
+        "2024-01-03T09:00:00Z",
+        "2023-01-10T12:00:00Z",
+        "not a date",
+    ]
+    .iter()
+    .map(|moved| format!("{:<22} {}", moved, Card::display_move_diff_at(moved, now)))
+    .collect();
+    assert_snapshot("move_diff", &lines.join("\n"));
+}
@@ -0,0 +1,60 @@
+//! Color codes of the tables, a separate test binary since the color switch is global
+mod common;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use common::fixture;
+use common::snapshot::assert_snapshot;
+use kaiten_cli::models::Card;
+use serde_json::json;
+
+const RED: &str = "\u{1b}[31m";
+
+fn colors() {
+    colored::control::set_override(true);
+}
+
+fn now() -> DateTime<Utc> {
+    "2024-01-10T12:00:00Z".parse().unwrap()
+}
+
+#[test]
+fn move_diff_is_red_after_a_day() {
+    colors();
+    assert_eq!(Card::display_move_diff_at("2024-01-10T07:00:00Z", now()), "5h");
+    assert_eq!(
+        Card::display_move_diff_at("2024-01-08T07:00:00Z", now()),
+        format!("{}2d5h\u{1b}[0m", RED)
+    );
+}
+
+#[test]
+fn overdue_date_is_red_until_done() {
+    colors();
+    let today = NaiveDate::from_ymd_opt(2024, 1, 10).unwrap();
+    let mut card = fixture("card_5.json");
+    card["due_date"] = json!("2024-01-09");
+    let overdue: Card = serde_json::from_value(card.clone()).unwrap();
+    assert_eq!(overdue.display_due_date_at(today), format!("{}2024-01-09\u{1b}[0m", RED));
+
+    card["state"] = json!(3);
+    let done: Card = serde_json::from_value(card).unwrap();
+    assert_eq!(done.display_due_date_at(today), "2024-01-09");
+}
+
+#[test]
+fn blocked_card_details() {
+    colors();
+    let mut card = fixture("card_5.json");
+    card["blocked"] = json!(true);
+    card["blockers"] = json!([{
+        "id": 1,
+        "reason": "Waiting for the API",
+        "blocker": { "id": 6, "username": "bob" },
+        "released": false,
+        "created": "2024-01-08T09:00:00Z",
+        "updated": "2024-01-08T09:00:00Z",
+        "card": null
+    }]);
+    let card: Card = serde_json::from_value(card).unwrap();
+    assert_snapshot("card_details_colors", &card.to_table_string_at(now().fixed_offset()));
+}
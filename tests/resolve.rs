@@ -0,0 +1,110 @@
+mod common;
+
+use common::fixture;
+use kaiten_cli::models::common::{find_by_name, Candidate};
+use kaiten_cli::models::{Board, Info, Space};
+use serde_json::json;
+use std::collections::HashMap;
+
+/// Fixture board 10 and board 11 with numeric column and lane titles
+fn info() -> Info {
+    let spaces: Vec<Space> = serde_json::from_value(fixture("spaces.json")).unwrap();
+    let other: Board = serde_json::from_value(json!({
+        "id": 11,
+        "space_id": 1,
+        "title": "Releases",
+        "columns": [
+            { "id": 300, "title": "2024", "board_id": 11, "sort_order": 1.0, "column_id": null, "subcolumns": null },
+            { "id": 301, "title": "1", "board_id": 11, "sort_order": 2.0, "column_id": null, "subcolumns": null },
+            { "id": 302, "title": "Queue", "board_id": 11, "sort_order": 3.0, "column_id": null, "subcolumns": null }
+        ],
+        "lanes": [
+            { "id": 400, "title": "100", "board_id": 11, "condition": 1, "sort_order": 1.0 }
+        ]
+    }))
+    .unwrap();
+    let board: Board = serde_json::from_value(fixture("board_10.json")).unwrap();
+    Info::from(
+        spaces.into_iter().map(|space| (space.get_id(), space)).collect(),
+        HashMap::from([(board.get_id(), board), (other.get_id(), other)]),
+        Vec::new(),
+        Vec::new(),
+    )
+}
+
+fn column(query: &str, board_id: Option<u32>) -> Result<u32, String> {
+    info().resolve_column(query, board_id).map(|col| col.get_id())
+}
+
+fn lane(query: &str, board_id: Option<u32>) -> Result<u32, String> {
+    info().resolve_lane(query, board_id).map(|lane| lane.get_id())
+}
+
+fn find(query: &str, titles: &[&str]) -> Result<u32, String> {
+    let candidates = titles
+        .iter()
+        .enumerate()
+        .map(|(idx, label)| {
+            let title = label.rsplit('/').next().unwrap().to_string();
+            Candidate::new(label.to_string(), title, idx as u32 + 1, idx as u32 + 1)
+        })
+        .collect();
+    find_by_name("column", query, candidates)
+}
+
+#[test]
+fn numeric_titles_are_found_by_name() {
+    assert_eq!(column("2024", Some(11)), Ok(300));
+    assert_eq!(column("1", Some(11)), Ok(301));
+    assert_eq!(lane("100", Some(11)), Ok(400));
+}
+
+#[test]
+fn ids_on_the_board_win() {
+    assert_eq!(column("302", Some(11)), Ok(302));
+    assert_eq!(column("102", Some(10)), Ok(102));
+    assert_eq!(lane("201", Some(10)), Ok(201));
+}
+
+#[test]
+fn ids_from_another_board_need_prefix() {
+    let err = column("100", Some(11)).unwrap_err();
+    assert!(err.contains("column with id 100 is on board 10, use `id:100`"), "{}", err);
+    let err = lane("200", Some(11)).unwrap_err();
+    assert!(err.contains("use `id:200`"), "{}", err);
+    assert_eq!(column("id:100", Some(11)), Ok(100));
+    assert_eq!(lane("id: 200", Some(11)), Ok(200));
+    assert!(column("id:999", Some(11)).unwrap_err().contains("No column with id 999"));
+    assert!(column("id:queue", Some(11)).unwrap_err().contains("is not a column id"));
+}
+
+#[test]
+fn without_board_only_ids_work() {
+    assert_eq!(column("100", None), Ok(100));
+    assert_eq!(column("300", None), Ok(300));
+    assert!(column("Queue", None).unwrap_err().contains("Board is not set"));
+}
+
+#[test]
+fn exact_names_beat_prefixes() {
+    assert_eq!(find("design", &["Design review", "Design"]), Ok(2));
+    assert_eq!(find("DESIGN REVIEW", &["Design review", "Design"]), Ok(1));
+    assert_eq!(find("готово", &["Готово", "Готово к релизу"]), Ok(1));
+}
+
+#[test]
+fn unique_prefix_is_found_and_shared_one_is_ambiguous() {
+    assert_eq!(find("dep", &["Deploy", "Design"]), Ok(1));
+    let err = find("de", &["Deploy", "Design"]).unwrap_err();
+    assert_eq!(err, "Ambiguous column `de`, use one of: Deploy (1), Design (2)");
+    let err = find("qa", &["Deploy", "Design"]).unwrap_err();
+    assert_eq!(err, "No column matches `qa`. Available: Deploy (1), Design (2)");
+}
+
+#[test]
+fn subcolumns_match_segment_by_segment() {
+    let titles = ["In progress", "In progress/Review", "Done", "Done/Review"];
+    assert_eq!(find("in/rev", &titles), Ok(2));
+    assert_eq!(find("Done/Review", &titles), Ok(4));
+    assert!(find("review", &titles).unwrap_err().starts_with("Ambiguous column `review`"));
+}
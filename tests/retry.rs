@@ -0,0 +1,156 @@
+use kaiten_cli::api::retry::RequestBudget;
+use kaiten_cli::api::{ApiClient, ClientSettings, RetryPolicy};
+use kaiten_cli::error::Error;
+use serde_json::json;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tiny_http::{Header, Response, Server};
+
+/// Server answering with `responses` in order, then with `200`.
+/// Returns the API url and the methods of the received requests
+fn scripted(responses: Vec<(u16, Option<&str>)>) -> (String, Arc<Mutex<Vec<String>>>) {
+    let server = Server::http("127.0.0.1:0").unwrap();
+    let port = server.server_addr().to_ip().unwrap().port();
+    let methods = Arc::new(Mutex::new(Vec::new()));
+    let received = Arc::clone(&methods);
+    let mut responses: VecDeque<(u16, Option<String>)> = responses
+        .into_iter()
+        .map(|(status, retry_after)| (status, retry_after.map(str::to_string)))
+        .collect();
+    thread::spawn(move || {
+        for request in server.incoming_requests() {
+            received.lock().unwrap().push(request.method().to_string());
+            let (status, retry_after) = responses.pop_front().unwrap_or((200, None));
+            let mut response = Response::from_string(json!({ "id": 1 }).to_string())
+                .with_status_code(status)
+                .with_header(Header::from_bytes("Content-Type", "application/json").unwrap());
+            if let Some(seconds) = retry_after {
+                response.add_header(Header::from_bytes("Retry-After", seconds).unwrap());
+            }
+            let _ = request.respond(response);
+        }
+    });
+    (format!("http://127.0.0.1:{}/api/latest/", port), methods)
+}
+
+fn client(url: &str) -> ApiClient {
+    let settings = ClientSettings {
+        retry: RetryPolicy {
+            max_retries: 2,
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_secs(30),
+        },
+        rate_limit: 0,
+        ..ClientSettings::default()
+    };
+    ApiClient::with_settings(url, "token", settings).unwrap()
+}
+
+#[test]
+fn backoff_doubles_with_jitter() {
+    let policy = RetryPolicy {
+        max_retries: 10,
+        base_delay: Duration::from_millis(100),
+        max_delay: Duration::from_secs(30),
+    };
+    for attempt in 0..8 {
+        let full = Duration::from_millis(100 * 2u64.pow(attempt));
+        for _ in 0..20 {
+            let delay = policy.backoff(attempt);
+            assert!(delay >= full / 2 && delay <= full, "attempt {}: {:?}", attempt, delay);
+        }
+    }
+}
+
+#[test]
+fn backoff_is_capped() {
+    let policy = RetryPolicy::default();
+    assert_eq!(policy.max_delay, Duration::from_secs(30));
+    for attempt in [6, 10, 31, 32, 1000, u32::MAX] {
+        let delay = policy.backoff(attempt);
+        assert!(
+            delay >= Duration::from_secs(15) && delay <= Duration::from_secs(30),
+            "attempt {}: {:?}",
+            attempt,
+            delay
+        );
+    }
+}
+
+#[tokio::test]
+async fn budget_spaces_requests_out() {
+    let budget = RequestBudget::per_second(20);
+    let started = Instant::now();
+    for _ in 0..5 {
+        budget.acquire().await;
+    }
+    // the first slot is free, four more take 50ms each
+    assert!(started.elapsed() >= Duration::from_millis(200), "{:?}", started.elapsed());
+
+    let unlimited = RequestBudget::per_second(0);
+    let started = Instant::now();
+    for _ in 0..5 {
+        unlimited.acquire().await;
+    }
+    assert!(started.elapsed() < Duration::from_millis(100), "{:?}", started.elapsed());
+}
+
+#[tokio::test]
+async fn budget_pause_holds_next_request() {
+    let budget = RequestBudget::per_second(0);
+    budget.pause(Duration::from_millis(300)).await;
+    let started = Instant::now();
+    budget.acquire().await;
+    assert!(started.elapsed() >= Duration::from_millis(250), "{:?}", started.elapsed());
+}
+
+#[tokio::test]
+async fn get_is_retried_on_server_errors() {
+    let (url, methods) = scripted(vec![(500, None), (503, None)]);
+    client(&url).get_data("cards/1").await.unwrap();
+    assert_eq!(methods.lock().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn retries_stop_after_max_retries() {
+    let (url, methods) = scripted(vec![(500, None), (500, None), (500, None), (500, None)]);
+    let err = client(&url).get_data("cards/1").await.unwrap_err();
+    assert!(matches!(err, Error::Server(_)), "{:?}", err);
+    assert_eq!(methods.lock().unwrap().len(), 3, "the first attempt and 2 retries");
+}
+
+#[tokio::test]
+async fn post_and_patch_are_not_retried_on_server_errors() {
+    let (url, methods) = scripted(vec![(500, None), (502, None)]);
+    let client = client(&url);
+    let err = client.post_data("cards", json!({ "title": "x" })).await.unwrap_err();
+    assert!(matches!(err, Error::Server(_)), "{:?}", err);
+    let err = client.patch_data("cards/1", json!({ "title": "x" })).await.unwrap_err();
+    assert!(matches!(err, Error::Server(_)), "{:?}", err);
+    assert_eq!(*methods.lock().unwrap(), vec!["POST", "PATCH"]);
+}
+
+#[tokio::test]
+async fn rate_limited_request_waits_for_retry_after() {
+    let (url, methods) = scripted(vec![(429, Some("1"))]);
+    let started = Instant::now();
+    // nothing was created, so even a POST is repeated
+    client(&url).post_data("cards", json!({ "title": "x" })).await.unwrap();
+    assert!(started.elapsed() >= Duration::from_secs(1), "{:?}", started.elapsed());
+    assert_eq!(*methods.lock().unwrap(), vec!["POST", "POST"]);
+}
+
+#[tokio::test]
+async fn retry_after_above_max_delay_fails_at_once() {
+    let (url, methods) = scripted(vec![(429, Some("60"))]);
+    let err = client(&url).get_data("cards/1").await.unwrap_err();
+    assert!(
+        matches!(err, Error::RateLimited { retry_after: Some(60), .. }),
+        "{:?}",
+        err
+    );
+    assert_eq!(err.hint().unwrap(), "Kaiten rate limit is hit, retry in 60 seconds");
+    assert_eq!(methods.lock().unwrap().len(), 1);
+}